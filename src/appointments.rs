@@ -0,0 +1,458 @@
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use time::{macros::format_description, Date, OffsetDateTime, PrimitiveDateTime};
+
+use crate::data::{AppointmentJSON, AppointmentStatus, OrdinanceType};
+
+/// Which appointments to show. Options that aren't given don't filter anything.
+#[derive(Args)]
+pub struct AppointmentFilter {
+    /// Only show appointments that haven't started yet
+    #[clap(long, conflicts_with = "past")]
+    upcoming: bool,
+
+    /// Only show appointments that have already started
+    #[clap(long)]
+    past: bool,
+
+    /// Only show appointments on or after this date, like 2024-01-31
+    #[clap(long, value_name = "DATE", value_parser = parse_date)]
+    from: Option<Date>,
+
+    /// Only show appointments on or before this date, like 2024-12-31
+    #[clap(long, value_name = "DATE", value_parser = parse_date)]
+    to: Option<Date>,
+
+    /// Comma separated ordinances to show: baptism, initiatory, endowment or sealing
+    #[clap(long, value_delimiter = ',')]
+    ordinance: Vec<OrdinanceType>,
+
+    /// Only show appointments at this temple, by id or part of its name
+    #[clap(long)]
+    temple: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum AppointmentSort {
+    /// Earliest first
+    Date,
+
+    /// Grouped by ordinance, earliest first within each
+    Type,
+}
+
+impl AppointmentFilter {
+    pub fn matches(&self, appointment: &AppointmentJSON, now: OffsetDateTime) -> bool {
+        let start = appointment.date_time().local();
+        let date = start.date();
+
+        (!self.upcoming || start >= now)
+            && (!self.past || start < now)
+            && self.from.is_none_or(|from| date >= from)
+            && self.to.is_none_or(|to| date <= to)
+            && (self.ordinance.is_empty()
+                || appointment
+                    .ordinance_type()
+                    .is_some_and(|o| self.ordinance.contains(&o)))
+            && self
+                .temple
+                .as_deref()
+                .is_none_or(|temple| at_temple(appointment, temple))
+    }
+
+    /// Keep the appointments that match, in the given order.
+    pub fn apply(
+        &self,
+        appointments: Vec<AppointmentJSON>,
+        sort: AppointmentSort,
+        now: OffsetDateTime,
+    ) -> Vec<AppointmentJSON> {
+        let mut appointments: Vec<AppointmentJSON> = appointments
+            .into_iter()
+            .filter(|a| self.matches(a, now))
+            .collect();
+        match sort {
+            AppointmentSort::Date => appointments.sort_by_key(|a| a.date_time().local()),
+            AppointmentSort::Type => {
+                // Types we don't know go after the ones we do.
+                appointments.sort_by_key(|a| {
+                    let ordinance = a.ordinance_type();
+                    (ordinance.is_none(), ordinance, a.date_time().local())
+                })
+            }
+        }
+        appointments
+    }
+}
+
+/// The first appointment that hasn't started yet and wasn't cancelled.
+pub fn next(appointments: &[AppointmentJSON], now: OffsetDateTime) -> Option<&AppointmentJSON> {
+    appointments
+        .iter()
+        .filter(|a| a.appointment_status != AppointmentStatus::Cancelled)
+        .filter(|a| a.date_time().local() >= now)
+        .min_by_key(|a| a.date_time().local())
+}
+
+fn at_temple(appointment: &AppointmentJSON, temple: &str) -> bool {
+    match temple.parse::<u32>() {
+        Ok(id) => appointment.temple_org_id == Some(id),
+        Err(_) => appointment
+            .temple_display_name()
+            .to_lowercase()
+            .contains(&temple.to_lowercase()),
+    }
+}
+
+/// Parse a date and time like 2024-01-31T09:00 or 2024-01-31 09:00.
+pub fn parse_date_time(s: &str) -> Result<PrimitiveDateTime, String> {
+    let format = format_description!("[year]-[month]-[day]T[hour]:[minute]");
+    PrimitiveDateTime::parse(&s.trim().replacen(' ', "T", 1), format).map_err(|_| {
+        format!(
+            "Invalid date and time {}. Use the format 2024-01-31T09:00",
+            s
+        )
+    })
+}
+
+fn parse_date(s: &str) -> Result<Date, String> {
+    Date::parse(s, format_description!("[year]-[month]-[day]"))
+        .map_err(|_| format!("Invalid date {}. Use the format 2024-01-31", s))
+}
+
+/// Totals from the appointments that were kept, meaning ones that have started and weren't
+/// cancelled. Several appointments on the same day count as one visit.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppointmentStats {
+    pub appointments: u32,
+    pub visits: u32,
+    pub by_ordinance: BTreeMap<OrdinanceType, u32>,
+    pub by_temple: BTreeMap<String, u32>,
+
+    /// Keyed by year and month, like 2024-03.
+    pub by_month: BTreeMap<String, u32>,
+
+    pub by_year: BTreeMap<i32, u32>,
+
+    /// Most months in a row with at least one visit.
+    pub longest_streak: Option<Streak>,
+
+    /// Days between one visit and the next, on average. None without at least two visits.
+    pub average_gap_days: Option<f64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Streak {
+    pub months: u32,
+
+    /// First day of the first and last months of the streak.
+    pub start: Date,
+    pub end: Date,
+}
+
+impl AppointmentStats {
+    pub fn new(appointments: &[AppointmentJSON], now: OffsetDateTime) -> Self {
+        let kept: Vec<&AppointmentJSON> = appointments
+            .iter()
+            .filter(|a| a.appointment_status != AppointmentStatus::Cancelled)
+            .filter(|a| a.date_time().local() < now)
+            .collect();
+
+        let mut by_ordinance = BTreeMap::new();
+        let mut by_temple = BTreeMap::new();
+        let mut by_month = BTreeMap::new();
+        let mut by_year = BTreeMap::new();
+        for appointment in &kept {
+            let date = appointment.date_time().date();
+            if let Some(ordinance) = appointment.ordinance_type() {
+                *by_ordinance.entry(ordinance).or_insert(0) += 1;
+            }
+            *by_temple
+                .entry(appointment.temple_display_name())
+                .or_insert(0) += 1;
+            *by_month
+                .entry(format!("{}-{:02}", date.year(), date.month() as u8))
+                .or_insert(0) += 1;
+            *by_year.entry(date.year()).or_insert(0) += 1;
+        }
+
+        let mut visits: Vec<Date> = kept.iter().map(|a| a.date_time().date()).collect();
+        visits.sort();
+        visits.dedup();
+
+        let average_gap_days = match (visits.first(), visits.last()) {
+            (Some(first), Some(last)) if visits.len() > 1 => {
+                Some((*last - *first).whole_days() as f64 / (visits.len() - 1) as f64)
+            }
+            _ => None,
+        };
+
+        Self {
+            appointments: kept.len() as u32,
+            visits: visits.len() as u32,
+            by_ordinance,
+            by_temple,
+            by_month,
+            by_year,
+            longest_streak: longest_streak(&visits),
+            average_gap_days,
+        }
+    }
+}
+
+/// The longest run of consecutive months among the sorted visit dates. The earliest run wins ties.
+fn longest_streak(visits: &[Date]) -> Option<Streak> {
+    let month_start = |date: &Date| date.replace_day(1).unwrap();
+    let mut months: Vec<Date> = visits.iter().map(month_start).collect();
+    months.dedup();
+
+    let mut longest: Option<Streak> = None;
+    let mut start = 0;
+    for i in 0..months.len() {
+        let continues = i + 1 < months.len() && next_month(months[i]) == months[i + 1];
+        if continues {
+            continue;
+        }
+
+        let length = (i - start + 1) as u32;
+        if longest.as_ref().is_none_or(|l| length > l.months) {
+            longest = Some(Streak {
+                months: length,
+                start: months[start],
+                end: months[i],
+            });
+        }
+        start = i + 1;
+    }
+
+    longest
+}
+
+/// First day of the month after the one `month` starts.
+fn next_month(month: Date) -> Date {
+    let (year, month) = match month.month() {
+        time::Month::December => (month.year() + 1, time::Month::January),
+        other => (month.year(), other.next()),
+    };
+    Date::from_calendar_date(year, month, 1).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::appointment;
+    use time::macros::{date, datetime};
+
+    /// An appointment at 9:00 on the given date, like 2024-03-05.
+    fn on(date: &str, ordinance: &str, status: &str) -> AppointmentJSON {
+        appointment(serde_json::json!({
+            "appointmentDateTime": format!("{}T17:00:00Z", date),
+            "appointmentType": ordinance,
+            "appointmentStatus": status,
+            "templeName": "Provo Utah Temple",
+        }))
+    }
+
+    /// A filter that lets everything through.
+    fn everything() -> AppointmentFilter {
+        AppointmentFilter {
+            upcoming: false,
+            past: false,
+            from: None,
+            to: None,
+            ordinance: vec![],
+            temple: None,
+        }
+    }
+
+    fn dates(appointments: &[AppointmentJSON]) -> Vec<Date> {
+        appointments.iter().map(|a| a.date_time().date()).collect()
+    }
+
+    fn sample() -> Vec<AppointmentJSON> {
+        vec![
+            on("2024-03-20", "PROXY_SEALING", "BOOKED"),
+            on("2024-01-10", "PROXY_ENDOWMENT", "COMPLETED"),
+            appointment(serde_json::json!({
+                "appointmentDateTime": "2024-02-19T17:00:00Z",
+                "appointmentType": "PROXY_BAPTISM",
+                "appointmentStatus": "COMPLETED",
+                "templeName": "Salt Lake Temple",
+                "templeOrgId": 2,
+            })),
+            on("2024-02-01", "PROXY_WASHING", "BOOKED"),
+        ]
+    }
+
+    #[test]
+    fn filters_by_date_ordinance_and_temple() {
+        let now = datetime!(2024-02-10 0:00 UTC);
+        let matching =
+            |filter: AppointmentFilter| dates(&filter.apply(sample(), AppointmentSort::Date, now));
+
+        assert_eq!(matching(everything()).len(), 4);
+        assert_eq!(
+            matching(AppointmentFilter {
+                from: Some(date!(2024 - 02 - 01)),
+                to: Some(date!(2024 - 02 - 19)),
+                ..everything()
+            }),
+            [date!(2024 - 02 - 01), date!(2024 - 02 - 19)]
+        );
+        assert_eq!(
+            matching(AppointmentFilter {
+                upcoming: true,
+                ..everything()
+            }),
+            [date!(2024 - 02 - 19), date!(2024 - 03 - 20)]
+        );
+        assert_eq!(
+            matching(AppointmentFilter {
+                past: true,
+                ..everything()
+            }),
+            [date!(2024 - 01 - 10), date!(2024 - 02 - 01)]
+        );
+
+        // Appointments of a type we don't know never match an ordinance filter.
+        assert_eq!(
+            matching(AppointmentFilter {
+                ordinance: vec![OrdinanceType::Baptism, OrdinanceType::Sealing],
+                ..everything()
+            }),
+            [date!(2024 - 02 - 19), date!(2024 - 03 - 20)]
+        );
+
+        for temple in ["salt", "SALT LAKE", "2"] {
+            assert_eq!(
+                matching(AppointmentFilter {
+                    temple: Some(temple.to_string()),
+                    ..everything()
+                }),
+                [date!(2024 - 02 - 19)],
+                "{}",
+                temple
+            );
+        }
+        assert_eq!(
+            matching(AppointmentFilter {
+                temple: Some("3".to_string()),
+                ..everything()
+            }),
+            []
+        );
+    }
+
+    #[test]
+    fn sorts_by_date_or_type() {
+        let now = datetime!(2024-02-10 0:00 UTC);
+        let by_date = everything().apply(sample(), AppointmentSort::Date, now);
+        assert_eq!(
+            dates(&by_date),
+            [
+                date!(2024 - 01 - 10),
+                date!(2024 - 02 - 01),
+                date!(2024 - 02 - 19),
+                date!(2024 - 03 - 20),
+            ]
+        );
+
+        let by_type = everything().apply(sample(), AppointmentSort::Type, now);
+        let types: Vec<Option<OrdinanceType>> =
+            by_type.iter().map(|a| a.ordinance_type()).collect();
+        assert_eq!(
+            types,
+            [
+                Some(OrdinanceType::Baptism),
+                Some(OrdinanceType::Endowment),
+                Some(OrdinanceType::Sealing),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn next_skips_past_and_cancelled_appointments() {
+        let appointments = [
+            on("2024-01-10", "PROXY_ENDOWMENT", "COMPLETED"),
+            on("2024-03-01", "PROXY_BAPTISM", "CANCELLED"),
+            on("2024-04-15", "PROXY_SEALING", "BOOKED"),
+            on("2024-03-20", "PROXY_INITIATORY", "BOOKED"),
+        ];
+
+        let upcoming = next(&appointments, datetime!(2024-02-10 0:00 UTC)).unwrap();
+        assert_eq!(upcoming.date_time().date(), date!(2024 - 03 - 20));
+        assert!(next(&appointments, datetime!(2024-05-01 0:00 UTC)).is_none());
+    }
+
+    #[test]
+    fn next_month_rolls_over_the_year() {
+        assert_eq!(next_month(date!(2023 - 12 - 01)), date!(2024 - 01 - 01));
+        assert_eq!(next_month(date!(2024 - 03 - 01)), date!(2024 - 04 - 01));
+    }
+
+    #[test]
+    fn longest_streak_spans_years_and_earliest_wins_ties() {
+        let visits = [
+            date!(2023 - 11 - 20),
+            date!(2023 - 12 - 02),
+            date!(2023 - 12 - 28),
+            date!(2024 - 01 - 15),
+            date!(2024 - 03 - 01),
+            date!(2024 - 04 - 01),
+            date!(2024 - 06 - 01),
+            date!(2024 - 07 - 01),
+            date!(2024 - 08 - 01),
+        ];
+
+        let streak = longest_streak(&visits).unwrap();
+        assert_eq!(streak.months, 3);
+        assert_eq!(streak.start, date!(2023 - 11 - 01));
+        assert_eq!(streak.end, date!(2024 - 01 - 01));
+
+        assert!(longest_streak(&[]).is_none());
+        assert_eq!(longest_streak(&[date!(2024 - 05 - 09)]).unwrap().months, 1);
+    }
+
+    #[test]
+    fn stats_only_count_kept_appointments() {
+        let appointments = [
+            on("2024-01-10", "PROXY_ENDOWMENT", "COMPLETED"),
+            on("2024-01-10", "PROXY_SEALING", "COMPLETED"),
+            on("2024-02-01", "PROXY_BAPTISM", "CANCELLED"),
+            on("2024-02-19", "PROXY_ENDOWMENT", "COMPLETED"),
+            on("2024-03-20", "PROXY_INITIATORY", "BOOKED"),
+            on("2024-12-01", "PROXY_ENDOWMENT", "BOOKED"),
+        ];
+        let stats = AppointmentStats::new(&appointments, datetime!(2024-06-01 0:00 UTC));
+
+        // The cancelled one and the one that hasn't happened yet are left out, and the two on
+        // January 10 are one visit.
+        assert_eq!(stats.appointments, 4);
+        assert_eq!(stats.visits, 3);
+        assert_eq!(stats.by_ordinance[&OrdinanceType::Endowment], 2);
+        assert!(!stats.by_ordinance.contains_key(&OrdinanceType::Baptism));
+        assert_eq!(stats.by_temple["Provo Utah Temple"], 4);
+        assert_eq!(stats.by_month["2024-01"], 2);
+        assert!(!stats.by_month.contains_key("2024-12"));
+        assert_eq!(stats.by_year[&2024], 4);
+        assert_eq!(stats.longest_streak.as_ref().unwrap().months, 3);
+
+        // January 10 to March 20 is 70 days, over two gaps.
+        assert_eq!(stats.average_gap_days, Some(35.0));
+    }
+
+    #[test]
+    fn no_average_gap_without_two_visits() {
+        let appointments = [
+            on("2024-01-10", "PROXY_ENDOWMENT", "COMPLETED"),
+            on("2024-01-10", "PROXY_SEALING", "COMPLETED"),
+        ];
+        let stats = AppointmentStats::new(&appointments, datetime!(2024-06-01 0:00 UTC));
+        assert_eq!(stats.visits, 1);
+        assert_eq!(stats.average_gap_days, None);
+    }
+}
@@ -18,21 +18,37 @@ static HEADER_CHANNEL: Lazy<(MutexedHeaderSender, MutexedHeaderReceiver)> = Lazy
     (Mutex::new(tx), Mutex::new(rx))
 });
 
+const API_URL: &str = "https://tos.churchofjesuschrist.org/api";
+
+/// Where API requests go. Set TEMPLES_API_URL to send them somewhere else, like a local mock server.
+fn default_api_url() -> String {
+    std::env::var("TEMPLES_API_URL").unwrap_or_else(|_| API_URL.to_string())
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Client {
     pub cookie: String,
+
+    /// Base URL of the API, without a trailing slash. Not saved, so it can change between runs.
+    #[serde(skip, default = "default_api_url")]
+    pub api_url: String,
 }
 
 impl Client {
     const FILENAME: &'static str = "client.bincode";
 
+    /// URL of an API endpoint, like `appointments`.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.api_url.trim_end_matches('/'), path)
+    }
+
     pub fn new() -> Result<Self> {
         // First, try to read a serialized version from file.
         if let Ok(file) = std::fs::File::open(Self::FILENAME) {
             let client: Self = bincode::deserialize_from(&file)?;
 
             // Try an HTTP request to make sure the unserialized client has correct data.
-            if let Ok(response) = ureq::get("https://tos.churchofjesuschrist.org/api/appointments")
+            if let Ok(response) = ureq::get(&client.url("appointments"))
                 .set("Cookie", &client.cookie)
                 .call()
             {
@@ -122,7 +138,10 @@ impl Client {
 
         let cookie = HEADER_CHANNEL.1.lock().unwrap().recv().unwrap();
 
-        let client = Self { cookie };
+        let client = Self {
+            cookie,
+            api_url: default_api_url(),
+        };
 
         let file = std::fs::File::create(Self::FILENAME)?;
         bincode::serialize_into(file, &client)?;
@@ -1,36 +1,120 @@
-use serde::{de, Deserialize, Deserializer, Serialize};
-use std::{fmt, fmt::Display, str::FromStr};
+use crate::locale::Lang;
+use crate::timezone::{self, DisplayTimezone};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::BTreeMap, fmt, fmt::Display, str::FromStr};
 use time::{
-    macros::format_description, serde::rfc3339, Date, OffsetDateTime, PrimitiveDateTime, Time,
+    format_description::well_known::Rfc3339, macros::format_description, serde::rfc3339, Date,
+    OffsetDateTime, PrimitiveDateTime, Time,
 };
+use time_tz::{Offset, OffsetDateTimeExt, OffsetResult, PrimitiveDateTimeExt, TimeZone, Tz};
+
+/// A moment in time along with the timezone it happened in, so it can be shown in the timezone of the
+/// temple or converted to any other timezone.
+#[derive(Debug, Clone, Copy)]
+pub struct ZonedDateTime {
+    date_time: OffsetDateTime,
+    timezone: &'static Tz,
+}
 
-#[derive(Debug, Clone)]
-pub struct Day {
-    pub date: OffsetDateTime,
-    pub sessions: SessionsJSON,
+impl ZonedDateTime {
+    /// Attach a timezone to a wall clock time. If the time is ambiguous because of a daylight saving
+    /// change the earlier moment is used.
+    pub fn from_local(local: PrimitiveDateTime, timezone: &'static Tz) -> Self {
+        let date_time = match local.assume_timezone(timezone) {
+            OffsetResult::Some(date_time) | OffsetResult::Ambiguous(date_time, _) => date_time,
+            OffsetResult::None => local.assume_timezone_utc(timezone), // Skipped by a daylight saving change.
+        };
+
+        Self {
+            date_time,
+            timezone,
+        }
+    }
+
+    /// A moment given with any offset, shown in the given timezone.
+    pub fn from_offset(date_time: OffsetDateTime, timezone: &'static Tz) -> Self {
+        Self {
+            date_time: date_time.to_timezone(timezone),
+            timezone,
+        }
+    }
+
+    /// Date and time as seen on a wall clock in this value's timezone.
+    pub fn local(&self) -> OffsetDateTime {
+        self.date_time
+    }
+
+    pub fn timezone(&self) -> &'static Tz {
+        self.timezone
+    }
+
+    /// Short name of the timezone at this moment, like MST or MDT.
+    pub fn abbreviation(&self) -> String {
+        self.timezone
+            .get_offset_utc(&self.date_time)
+            .name()
+            .to_string()
+    }
+
+    /// The same moment in time, in a different timezone.
+    pub fn to_timezone(self, timezone: &'static Tz) -> Self {
+        Self {
+            date_time: self.date_time.to_timezone(timezone),
+            timezone,
+        }
+    }
+
+    pub fn date(&self) -> Date {
+        self.date_time.date()
+    }
 }
 
-impl Display for Day {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let format = format_description!("[month repr:short] [day], [year] ([weekday repr:short])");
-        write!(
-            f,
-            "{}\n{}",
-            self.date.format(&format).unwrap(),
-            self.sessions
-        )
+/// Serialized as an RFC 3339 timestamp with the offset of its timezone at that moment.
+impl Serialize for ZonedDateTime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.date_time.format(&Rfc3339).unwrap())
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct SessionsJSON {
-    pub session_list: Vec<Session>,
+#[derive(Serialize, Debug, Clone)]
+pub struct Day {
+    pub date: Date, // In the timezone of the sessions
+    pub sessions: Vec<Session>,
 }
 
-impl Display for SessionsJSON {
+impl Day {
+    /// Convert sessions into another timezone, moving them to whichever day they fall on there.
+    /// Every day is kept even if it ends up with no sessions so closed days still show up.
+    pub fn in_timezone(days: &[Day], timezone: &'static Tz) -> Vec<Day> {
+        let mut by_date: BTreeMap<Date, Vec<Session>> =
+            days.iter().map(|d| (d.date, vec![])).collect();
+
+        for session in days.iter().flat_map(|d| &d.sessions) {
+            let session = Session {
+                time: session.time.to_timezone(timezone),
+                ..session.clone()
+            };
+            by_date
+                .entry(session.time.date())
+                .or_default()
+                .push(session);
+        }
+
+        by_date
+            .into_iter()
+            .map(|(date, sessions)| Day { date, sessions })
+            .collect()
+    }
+}
+
+impl Display for Day {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for session in &self.session_list {
+        let format = format_description!("[month repr:short] [day], [year] ([weekday repr:short])");
+        writeln!(f, "{}", self.date.format(&format).unwrap())?;
+        for session in &self.sessions {
             writeln!(f, "{}", session)?;
         }
 
@@ -39,22 +123,56 @@ impl Display for SessionsJSON {
 }
 
 #[derive(Deserialize, Debug, Clone)]
-pub struct Session {
+#[serde(rename_all = "camelCase")]
+pub struct SessionsJSON {
+    pub session_list: Vec<SessionJSON>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SessionJSON {
     #[serde(deserialize_with = "deserialize_primitivedatetime")]
     pub time: PrimitiveDateTime, // In the timezone of the temple (according to text on the website)
 
     pub details: SessionDetails,
 }
 
+impl SessionJSON {
+    pub fn into_session(self, timezone: &'static Tz, fetched_at: OffsetDateTime) -> Session {
+        Session {
+            time: ZonedDateTime::from_local(self.time, timezone),
+            details: self.details,
+            fetched_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub time: ZonedDateTime,
+    pub details: SessionDetails,
+
+    /// When the seat counts were fetched, since they change as people book.
+    #[serde(with = "rfc3339")]
+    pub fetched_at: OffsetDateTime,
+}
+
 impl Display for Session {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let format = format_description!("[hour repr:12]:[minute] [period]");
         write!(
             f,
             "{} - remaining seats: {}",
-            self.time.format(&format).unwrap(),
-            self.details.remaining_online_seats_available
-        )
+            self.time.local().format(&format).unwrap(),
+            self.details.remaining_seats()
+        )?;
+
+        let summary = self.details.summary(Lang::En);
+        if !summary.is_empty() {
+            write!(f, " ({})", summary)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -204,10 +322,73 @@ mod serde_status {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionDetails {
     pub remaining_online_seats_available: i32,
+
+    #[serde(default, alias = "totalCapacity", alias = "sessionCapacity")]
+    pub total_seats: Option<i32>,
+
+    #[serde(default, alias = "remainingWalkInSeatsAvailable")]
+    pub walk_in_seats_available: Option<i32>,
+
+    #[serde(default, alias = "sessionLanguage")]
+    pub language: Option<String>,
+
+    #[serde(default, alias = "isSpecialSession")]
+    pub special_session: bool,
+
+    #[serde(default, alias = "isClosed", alias = "sessionClosed")]
+    pub closed: bool,
+}
+
+/// Whether a session can still be booked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Availability {
+    Open(u32),
+    Full,
+    Closed,
+}
+
+impl SessionDetails {
+    /// Seats that can still be booked online. The API goes negative when a session has been overbooked,
+    /// usually because the temple added walk-ins or held seats back after people booked online, so
+    /// anything below zero just means the session is full.
+    pub fn remaining_seats(&self) -> u32 {
+        self.remaining_online_seats_available.max(0) as u32
+    }
+
+    pub fn availability(&self) -> Availability {
+        match self.remaining_seats() {
+            _ if self.closed => Availability::Closed,
+            0 => Availability::Full,
+            remaining => Availability::Open(remaining),
+        }
+    }
+
+    /// How full the session is, from 0 to 100. Only known if the API sent the total number of seats.
+    pub fn fill_percentage(&self) -> Option<u32> {
+        match self.total_seats {
+            Some(total) if total > 0 => {
+                let taken = (total - self.remaining_online_seats_available).clamp(0, total);
+                Some((taken * 100 / total) as u32)
+            }
+            _ => None,
+        }
+    }
+
+    /// Short description of everything besides the remaining seats, like "75% full, Spanish, special session".
+    pub fn summary(&self, lang: Lang) -> String {
+        lang.session_details(
+            self.total_seats,
+            self.fill_percentage(),
+            self.walk_in_seats_available,
+            self.language.as_deref(),
+            self.special_session,
+            self.closed,
+        )
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -220,8 +401,98 @@ pub struct AppointmentJSON {
 
     #[serde(deserialize_with = "deserialize_time")]
     appointment_time: Time, // For some reason the time in appointment_date_time seems to be wrong. But this parameter is right in the timezone of the temple.
+
+    #[serde(skip)]
+    timezone: Option<&'static Tz>, // Filled in from the temple list since the API doesn't send it.
+
+    #[serde(default)]
+    pub temple_org_id: Option<u32>,
+
+    #[serde(default)]
+    pub temple_name: Option<String>,
+
+    #[serde(default, alias = "status", with = "serde_appointment_status")]
+    pub appointment_status: AppointmentStatus,
+
+    #[serde(default)]
+    pub confirmation_number: Option<String>,
+
+    #[serde(default, alias = "guestCount")]
+    pub number_of_guests: Option<u32>,
+
+    #[serde(default)]
+    pub proxy_names: Vec<String>,
+
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AppointmentStatus {
+    #[default]
+    Booked,
+    Cancelled,
+    Completed,
+
+    /// A status we don't know about yet, so one new status doesn't stop every appointment from loading.
+    Unknown,
+}
+
+impl Display for AppointmentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppointmentStatus::Booked => write!(f, "Booked"),
+            AppointmentStatus::Cancelled => write!(f, "Cancelled"),
+            AppointmentStatus::Completed => write!(f, "Completed"),
+            AppointmentStatus::Unknown => write!(f, "Unknown"),
+        }
+    }
 }
 
+mod serde_appointment_status {
+    use crate::data::AppointmentStatus;
+    use serde::{de, Deserializer};
+    use std::fmt;
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<AppointmentStatus, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct JsonStringVisitor;
+
+        impl<'de> de::Visitor<'de> for JsonStringVisitor {
+            type Value = AppointmentStatus;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string containing an appointment status, or null")
+            }
+
+            // Null means the same as leaving the status out.
+            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(AppointmentStatus::default())
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match v.to_uppercase().as_str() {
+                    "BOOKED" | "SCHEDULED" | "CONFIRMED" => Ok(AppointmentStatus::Booked),
+                    "CANCELLED" | "CANCELED" => Ok(AppointmentStatus::Cancelled),
+                    "COMPLETED" | "ATTENDED" => Ok(AppointmentStatus::Completed),
+                    _ => Ok(AppointmentStatus::Unknown),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(JsonStringVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum OrdinanceType {
     Baptism,
     Initiatory,
@@ -244,45 +515,146 @@ impl FromStr for OrdinanceType {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_uppercase().as_str() {
+        let s = s.to_uppercase();
+        match s.strip_prefix("PROXY_").unwrap_or(&s) {
             "BAPTISM" => Ok(OrdinanceType::Baptism),
             "INITIATORY" => Ok(OrdinanceType::Initiatory),
             "ENDOWMENT" => Ok(OrdinanceType::Endowment),
             "SEALING" => Ok(OrdinanceType::Sealing),
-            "PROXY_SEALING" => Ok(OrdinanceType::Sealing),
             o => Err(format!("Unknown ordinance type {}", o)),
         }
     }
 }
 
+impl OrdinanceType {
+    /// How the API names the ordinance when booking or fetching sessions.
+    pub fn appointment_type(&self) -> &'static str {
+        match self {
+            OrdinanceType::Baptism => "PROXY_BAPTISM",
+            OrdinanceType::Initiatory => "PROXY_INITIATORY",
+            OrdinanceType::Endowment => "PROXY_ENDOWMENT",
+            OrdinanceType::Sealing => "PROXY_SEALING",
+        }
+    }
+}
+
 impl AppointmentJSON {
-    pub fn ordinance_type(&self) -> OrdinanceType {
-        self.appointment_type
-            .parse()
-            .expect("Unable to parse appointment type")
+    /// The ordinance the appointment is for, or `None` if the API sent a type we don't know.
+    pub fn ordinance_type(&self) -> Option<OrdinanceType> {
+        self.appointment_type.parse().ok()
+    }
+
+    /// The ordinance's name in the given language, falling back to the type the API sent.
+    pub fn ordinance_name(&self, lang: Lang) -> String {
+        match self.ordinance_type() {
+            Some(ordinance) => lang.ordinance(&ordinance).to_string(),
+            None => self.appointment_type.clone(),
+        }
+    }
+
+    /// Fill in the details we need from the temple the appointment is at.
+    pub fn set_temple(&mut self, temple: &Temple) {
+        if self.temple_name.is_none() {
+            self.temple_name = Some(temple.name.clone());
+        }
+        self.timezone = Some(temple.timezone());
+    }
+
+    /// When the appointment is, in the timezone of the temple. Falls back to the local timezone if we
+    /// don't know which temple the appointment is at.
+    pub fn date_time(&self) -> ZonedDateTime {
+        // Only the date part of appointment_date_time can be trusted, see the comment on appointment_time.
+        let local =
+            PrimitiveDateTime::new(self.appointment_date_time.date(), self.appointment_time);
+        ZonedDateTime::from_local(
+            local,
+            self.timezone.unwrap_or_else(timezone::local_timezone),
+        )
+    }
+
+    /// Display the appointment in the given language, with its time converted to the given timezone.
+    pub fn display_in(&self, timezone: DisplayTimezone, lang: Lang) -> AppointmentDisplay<'_> {
+        AppointmentDisplay {
+            appointment: self,
+            timezone,
+            lang,
+        }
+    }
+
+    /// Name of the temple the appointment is at, falling back to its id if the API didn't send a name.
+    pub fn temple_display_name(&self) -> String {
+        match (&self.temple_name, self.temple_org_id) {
+            (Some(name), _) => name.clone(),
+            (None, Some(id)) => format!("Temple {}", id),
+            (None, None) => "Unknown temple".to_string(),
+        }
+    }
+
+    /// Number of people attending besides the patron. Proxy names are the people the ordinances are
+    /// done for, not people coming along, so they don't count.
+    pub fn guest_count(&self) -> u32 {
+        self.number_of_guests.unwrap_or(0)
     }
 }
 
 impl Display for AppointmentJSON {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let date_format = format_description!("[month repr:short] [day padding:none], [year]");
-        let time_format = format_description!("[hour repr:12 padding:none]:[minute] [period]");
+        self.display_in(DisplayTimezone::Temple, Lang::En).fmt(f)
+    }
+}
+
+pub struct AppointmentDisplay<'a> {
+    appointment: &'a AppointmentJSON,
+    timezone: DisplayTimezone,
+    lang: Lang,
+}
+
+impl Display for AppointmentDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let appointment = self.appointment;
+        let date_time = appointment.date_time();
+        let date_time = date_time.to_timezone(self.timezone.resolve(date_time.timezone()));
 
         write!(
             f,
-            "{} at {} - {}",
-            self.appointment_date_time.format(&date_format).unwrap(),
-            self.appointment_time.format(&time_format).unwrap(),
-            self.ordinance_type()
-        )
+            "{}",
+            self.lang.appointment(
+                date_time.local(),
+                &date_time.abbreviation(),
+                &appointment.ordinance_name(self.lang),
+                &appointment.temple_display_name(),
+                appointment.appointment_status
+            )
+        )?;
+
+        if let Some(confirmation) = &appointment.confirmation_number {
+            write!(f, ", {}", self.lang.confirmation(confirmation))?;
+        }
+
+        match appointment.guest_count() {
+            0 => {}
+            n => write!(f, ", {}", self.lang.guests(n))?,
+        }
+
+        if !appointment.proxy_names.is_empty() {
+            write!(f, " [{}]", appointment.proxy_names.join(", "))?;
+        }
+
+        if let Some(language) = &appointment.language {
+            write!(f, ", {}", language)?;
+        }
+
+        Ok(())
     }
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 pub enum FetchRange {
     ThisMonthFromToday,
     NumberOfDays(u32),
+
+    /// Only this date, in the timezone of the temple.
+    Day(Date),
 }
 
 #[derive(Debug, Clone)]
@@ -308,9 +680,147 @@ pub struct Temple {
 
     pub country: String,
 
-    location: String,
+    pub location: String,
     temple_name_id: String,
     city: String,
     state_region: String,
     sort_date: String,
 }
+
+impl Temple {
+    pub fn timezone(&self) -> &'static Tz {
+        timezone::temple_timezone(&self.country, &self.state_region, &self.city)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn temple(id: u32, name: &str) -> Temple {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "status": "OPERATING",
+            "date": "15 February 1972",
+            "templeOrgId": id,
+            "country": "United States",
+            "stateRegion": "Utah",
+            "city": "Provo",
+            "location": "Provo, Utah",
+            "templeNameId": "provo",
+            "sortDate": "",
+        }))
+        .unwrap()
+    }
+
+    /// An endowment appointment on March 5, 2024 at 9:00, with any fields given replaced.
+    pub(crate) fn appointment(json: serde_json::Value) -> AppointmentJSON {
+        let mut fields = serde_json::json!({
+            "appointmentType": "PROXY_ENDOWMENT",
+            "appointmentDateTime": "2024-03-05T17:00:00Z",
+            "appointmentTime": "09:00",
+        });
+        fields
+            .as_object_mut()
+            .unwrap()
+            .extend(json.as_object().unwrap().clone());
+        serde_json::from_value(fields).unwrap()
+    }
+
+    /// A day of sessions with this many seats left, or closed ones where `seats` is None.
+    pub(crate) fn day(temple: &Temple, date: Date, sessions: &[(time::Time, Option<i32>)]) -> Day {
+        Day {
+            date,
+            sessions: sessions
+                .iter()
+                .map(|(time, seats)| Session {
+                    time: ZonedDateTime::from_local(date.with_time(*time), temple.timezone()),
+                    details: serde_json::from_value(serde_json::json!({
+                        "remainingOnlineSeatsAvailable": seats.unwrap_or(0),
+                        "closed": seats.is_none(),
+                    }))
+                    .unwrap(),
+                    fetched_at: OffsetDateTime::UNIX_EPOCH,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn late_sessions_move_to_the_next_day() {
+        use time::macros::{date, time};
+
+        let temple = temple(1, "Provo Utah Temple");
+        let days = [
+            day(
+                &temple,
+                date!(2024 - 03 - 05),
+                &[(time!(9:00), Some(1)), (time!(22:30), Some(2))],
+            ),
+            day(&temple, date!(2024 - 03 - 06), &[(time!(9:00), Some(3))]),
+            day(&temple, date!(2024 - 03 - 07), &[(time!(23:00), None)]),
+        ];
+
+        let eastern = Day::in_timezone(&days, time_tz::timezones::db::america::NEW_YORK);
+        let times: Vec<(Date, Vec<time::Time>)> = eastern
+            .iter()
+            .map(|d| {
+                (
+                    d.date,
+                    d.sessions.iter().map(|s| s.time.local().time()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            times,
+            [
+                (date!(2024 - 03 - 05), vec![time!(11:00)]),
+                (date!(2024 - 03 - 06), vec![time!(0:30), time!(11:00)]),
+                // Still listed, so the closed day shows up.
+                (date!(2024 - 03 - 07), vec![]),
+                (date!(2024 - 03 - 08), vec![time!(1:00)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_appointment_types_keep_their_name() {
+        let unknown = appointment(serde_json::json!({ "appointmentType": "PROXY_WASHING" }));
+        assert_eq!(unknown.ordinance_type(), None);
+        assert_eq!(unknown.ordinance_name(Lang::Es), "PROXY_WASHING");
+        assert!(unknown
+            .display_in(DisplayTimezone::Temple, Lang::En)
+            .to_string()
+            .contains("PROXY_WASHING"));
+
+        let endowment = appointment(serde_json::json!({}));
+        assert_eq!(endowment.ordinance_type(), Some(OrdinanceType::Endowment));
+        assert_eq!(endowment.ordinance_name(Lang::Es), "Investidura");
+    }
+
+    #[test]
+    fn proxy_names_are_not_guests() {
+        let with_proxies = appointment(serde_json::json!({ "proxyNames": ["A", "B", "C"] }));
+        assert_eq!(with_proxies.guest_count(), 0);
+
+        let with_guests =
+            appointment(serde_json::json!({ "proxyNames": ["A"], "numberOfGuests": 2 }));
+        assert_eq!(with_guests.guest_count(), 2);
+    }
+
+    #[test]
+    fn appointment_statuses() {
+        let status = |status: serde_json::Value| {
+            appointment(serde_json::json!({ "appointmentStatus": status })).appointment_status
+        };
+        assert_eq!(status("SCHEDULED".into()), AppointmentStatus::Booked);
+        assert_eq!(status("canceled".into()), AppointmentStatus::Cancelled);
+        assert_eq!(status("ATTENDED".into()), AppointmentStatus::Completed);
+        assert_eq!(status("NO_SHOW".into()), AppointmentStatus::Unknown);
+        assert_eq!(status(serde_json::Value::Null), AppointmentStatus::Booked);
+        assert_eq!(
+            appointment(serde_json::json!({})).appointment_status,
+            AppointmentStatus::Booked
+        );
+    }
+}
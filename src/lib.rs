@@ -0,0 +1,46 @@
+//! Fetch temple data, appointments and session schedules, and write schedules in a variety of formats.
+//!
+//! Schedules are written by the [`OutputWriter`](visualize::OutputWriter)s in a
+//! [`FormatRegistry`](visualize::FormatRegistry). Register your own writer to add a format:
+//!
+//! ```
+//! use std::io::Write;
+//! use temples::{
+//!     visualize::{FormatRegistry, OutputOptions, OutputWriter, ScheduleGrid},
+//!     Temple,
+//! };
+//!
+//! struct OpenSeats;
+//!
+//! impl OutputWriter for OpenSeats {
+//!     fn extension(&self) -> &'static str {
+//!         "txt"
+//!     }
+//!
+//!     fn write_output(
+//!         &self,
+//!         grid: &ScheduleGrid,
+//!         temple: &Temple,
+//!         _options: &OutputOptions,
+//!         output: &mut dyn Write,
+//!     ) -> anyhow::Result<()> {
+//!         writeln!(output, "{}: {} open seats", temple.name, grid.stats.open_seats)?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut formats = FormatRegistry::default();
+//! formats.register("seats", OpenSeats);
+//! assert!(formats.names().any(|name| name == "seats"));
+//! ```
+
+pub mod appointments;
+pub mod client;
+pub mod data;
+pub mod locale;
+pub mod network;
+pub mod snapshot;
+pub mod timezone;
+pub mod visualize;
+
+pub use data::Temple;
@@ -0,0 +1,1029 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use time::{Date, Duration, Month, OffsetDateTime, Time, Weekday};
+
+use crate::data::{AppointmentStatus, OrdinanceType, Status};
+use crate::snapshot::ChangeKind;
+
+// Translations are bundled with the binary like the timezone tables. Each language also picks how times
+// are written: English uses a 12 hour clock, Spanish and Portuguese use a 24 hour clock.
+
+/// Language that dates, times and labels are shown in.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    /// English
+    #[default]
+    En,
+
+    /// Spanish
+    Es,
+
+    /// Portuguese
+    Pt,
+}
+
+/// Labels that don't have any values filled in. HTML templates get these as `text`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Labels {
+    pub available_slots: &'static str,
+    pub open_endowment_seats: &'static str,
+    pub full: &'static str,
+    pub closed: &'static str,
+    pub open: &'static str,
+    pub no_session: &'static str,
+    pub special: &'static str,
+    pub special_session: &'static str,
+    pub legend: &'static str,
+    pub from: &'static str,
+    pub to: &'static str,
+    pub at_least: &'static str,
+    pub seats: &'static str,
+    pub yes: &'static str,
+
+    // Spreadsheet sheets and headers
+    pub summary: &'static str,
+    pub sessions: &'static str,
+    pub open_seats: &'static str,
+    pub open_sessions: &'static str,
+    pub open_seats_by_day: &'static str,
+    pub open_seats_by_hour: &'static str,
+    pub best_times: &'static str,
+    pub fully_booked: &'static str,
+    pub day: &'static str,
+    pub hour: &'static str,
+    pub date: &'static str,
+    pub time: &'static str,
+    pub remaining_seats: &'static str,
+    pub status: &'static str,
+    pub details: &'static str,
+    pub fetched_at_utc: &'static str,
+
+    // Schedule diffs
+    pub session_changes: &'static str,
+    pub change: &'static str,
+    pub before: &'static str,
+    pub after: &'static str,
+    pub difference: &'static str,
+    pub no_changes: &'static str,
+
+    // Temple list
+    pub name: &'static str,
+    pub dedicated: &'static str,
+    pub id: &'static str,
+
+    // Appointment list
+    pub ordinance: &'static str,
+    pub temple: &'static str,
+    pub confirmation: &'static str,
+    pub guests: &'static str,
+    pub language: &'static str,
+    pub no_upcoming_appointments: &'static str,
+    pub dry_run: &'static str,
+    pub not_booked: &'static str,
+    pub booked: &'static str,
+    pub not_cancelled: &'static str,
+    pub cancelled_appointment: &'static str,
+    pub not_rescheduled: &'static str,
+    pub rescheduled: &'static str,
+
+    // Appointment stats
+    pub by_ordinance: &'static str,
+    pub by_temple: &'static str,
+    pub by_month: &'static str,
+    pub by_year: &'static str,
+}
+
+const EN: Labels = Labels {
+    available_slots: "Available slots for endowment",
+    open_endowment_seats: "Open endowment seats",
+    full: "Full",
+    closed: "Closed",
+    open: "Open",
+    no_session: "No session",
+    special: "Special",
+    special_session: "Special session",
+    legend: "Legend",
+    from: "From",
+    to: "To",
+    at_least: "At least",
+    seats: "seats",
+    yes: "Yes",
+    summary: "Summary",
+    sessions: "Sessions",
+    open_seats: "Open seats",
+    open_sessions: "Open sessions",
+    open_seats_by_day: "Open seats by day",
+    open_seats_by_hour: "Open seats by hour",
+    best_times: "Best times",
+    fully_booked: "Fully booked",
+    day: "Day",
+    hour: "Hour",
+    date: "Date",
+    time: "Time",
+    remaining_seats: "Remaining seats",
+    status: "Status",
+    details: "Details",
+    fetched_at_utc: "Fetched at (UTC)",
+    session_changes: "Session changes",
+    change: "Change",
+    before: "Before",
+    after: "After",
+    difference: "Difference",
+    no_changes: "No sessions changed",
+    name: "Name",
+    dedicated: "Dedicated",
+    id: "Id",
+    ordinance: "Ordinance",
+    temple: "Temple",
+    confirmation: "Confirmation",
+    guests: "Guests",
+    language: "Language",
+    no_upcoming_appointments: "No upcoming appointments",
+    dry_run: "Dry run, so nothing was booked. This would be sent:",
+    not_booked: "Nothing was booked.",
+    booked: "Booked:",
+    not_cancelled: "Nothing was cancelled.",
+    cancelled_appointment: "Cancelled:",
+    not_rescheduled: "Nothing was changed.",
+    rescheduled: "Rescheduled:",
+    by_ordinance: "By ordinance",
+    by_temple: "By temple",
+    by_month: "By month",
+    by_year: "By year",
+};
+
+const ES: Labels = Labels {
+    available_slots: "Horarios disponibles para la investidura",
+    open_endowment_seats: "Asientos libres para la investidura",
+    full: "Lleno",
+    closed: "Cerrado",
+    open: "Disponible",
+    no_session: "Sin sesión",
+    special: "Especial",
+    special_session: "Sesión especial",
+    legend: "Leyenda",
+    from: "Desde",
+    to: "Hasta",
+    at_least: "Al menos",
+    seats: "asientos",
+    yes: "Sí",
+    summary: "Resumen",
+    sessions: "Sesiones",
+    open_seats: "Asientos libres",
+    open_sessions: "Sesiones disponibles",
+    open_seats_by_day: "Asientos libres por día",
+    open_seats_by_hour: "Asientos libres por hora",
+    best_times: "Mejores horarios",
+    fully_booked: "Completo",
+    day: "Día",
+    hour: "Hora",
+    date: "Fecha",
+    time: "Hora",
+    remaining_seats: "Asientos restantes",
+    status: "Estado",
+    details: "Detalles",
+    fetched_at_utc: "Consultado (UTC)",
+    session_changes: "Cambios en las sesiones",
+    change: "Cambio",
+    before: "Antes",
+    after: "Después",
+    difference: "Diferencia",
+    no_changes: "Ninguna sesión cambió",
+    name: "Nombre",
+    dedicated: "Dedicado",
+    id: "Id",
+    ordinance: "Ordenanza",
+    temple: "Templo",
+    confirmation: "Confirmación",
+    guests: "Invitados",
+    language: "Idioma",
+    no_upcoming_appointments: "No hay citas próximas",
+    dry_run: "Prueba, así que no se reservó nada. Esto se enviaría:",
+    not_booked: "No se reservó nada.",
+    booked: "Reservada:",
+    not_cancelled: "No se canceló nada.",
+    cancelled_appointment: "Cancelada:",
+    not_rescheduled: "No se cambió nada.",
+    rescheduled: "Cambiada:",
+    by_ordinance: "Por ordenanza",
+    by_temple: "Por templo",
+    by_month: "Por mes",
+    by_year: "Por año",
+};
+
+const PT: Labels = Labels {
+    available_slots: "Horários disponíveis para a investidura",
+    open_endowment_seats: "Lugares livres para a investidura",
+    full: "Lotado",
+    closed: "Fechado",
+    open: "Disponível",
+    no_session: "Sem sessão",
+    special: "Especial",
+    special_session: "Sessão especial",
+    legend: "Legenda",
+    from: "De",
+    to: "Até",
+    at_least: "Pelo menos",
+    seats: "lugares",
+    yes: "Sim",
+    summary: "Resumo",
+    sessions: "Sessões",
+    open_seats: "Lugares livres",
+    open_sessions: "Sessões disponíveis",
+    open_seats_by_day: "Lugares livres por dia",
+    open_seats_by_hour: "Lugares livres por hora",
+    best_times: "Melhores horários",
+    fully_booked: "Lotado",
+    day: "Dia",
+    hour: "Hora",
+    date: "Data",
+    time: "Horário",
+    remaining_seats: "Lugares restantes",
+    status: "Situação",
+    details: "Detalhes",
+    fetched_at_utc: "Consultado (UTC)",
+    session_changes: "Alterações nas sessões",
+    change: "Alteração",
+    before: "Antes",
+    after: "Depois",
+    difference: "Diferença",
+    no_changes: "Nenhuma sessão mudou",
+    name: "Nome",
+    dedicated: "Dedicado",
+    id: "Id",
+    ordinance: "Ordenança",
+    temple: "Templo",
+    confirmation: "Confirmação",
+    guests: "Convidados",
+    language: "Idioma",
+    no_upcoming_appointments: "Nenhum agendamento futuro",
+    dry_run: "Teste, então nada foi agendado. Isto seria enviado:",
+    not_booked: "Nada foi agendado.",
+    booked: "Agendado:",
+    not_cancelled: "Nada foi cancelado.",
+    cancelled_appointment: "Cancelado:",
+    not_rescheduled: "Nada foi alterado.",
+    rescheduled: "Remarcado:",
+    by_ordinance: "Por ordenança",
+    by_temple: "Por templo",
+    by_month: "Por mês",
+    by_year: "Por ano",
+};
+
+impl Lang {
+    pub fn labels(self) -> &'static Labels {
+        match self {
+            Lang::En => &EN,
+            Lang::Es => &ES,
+            Lang::Pt => &PT,
+        }
+    }
+
+    /// Language tag for documents, like the `lang` attribute in HTML.
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Es => "es",
+            Lang::Pt => "pt",
+        }
+    }
+
+    /// Wall clock time like 9:00 AM or 09:00.
+    pub fn time(self, time: Time) -> String {
+        match self {
+            Lang::En => {
+                let hour = match time.hour() % 12 {
+                    0 => 12,
+                    hour => hour,
+                };
+                let period = if time.hour() < 12 { "AM" } else { "PM" };
+                format!("{}:{:02} {}", hour, time.minute(), period)
+            }
+            Lang::Es | Lang::Pt => format!("{:02}:{:02}", time.hour(), time.minute()),
+        }
+    }
+
+    /// Date like Oct 4, 2026 or 4 oct 2026.
+    pub fn date(self, date: Date) -> String {
+        match self {
+            Lang::En => format!(
+                "{} {}, {}",
+                self.month_short(date.month()),
+                date.day(),
+                date.year()
+            ),
+            Lang::Es | Lang::Pt => format!(
+                "{} {} {}",
+                date.day(),
+                self.month_short(date.month()),
+                date.year()
+            ),
+        }
+    }
+
+    /// Date with the month spelled out, like October 4, 2026 or 4 de octubre de 2026.
+    pub fn long_date(self, date: Date) -> String {
+        match self {
+            Lang::En => format!(
+                "{} {}, {}",
+                self.month_long(date.month()),
+                date.day(),
+                date.year()
+            ),
+            Lang::Es | Lang::Pt => format!(
+                "{} de {} de {}",
+                date.day(),
+                self.month_long(date.month()),
+                date.year()
+            ),
+        }
+    }
+
+    /// Month and day without the year, like Oct 4 or 4 oct.
+    pub fn month_day(self, date: Date) -> String {
+        match self {
+            Lang::En => format!("{} {}", self.month_short(date.month()), date.day()),
+            Lang::Es | Lang::Pt => format!("{} {}", date.day(), self.month_short(date.month())),
+        }
+    }
+
+    /// Weekday, month and day, like Sun Oct 4 or dom 4 oct.
+    pub fn day_label(self, date: Date) -> String {
+        format!(
+            "{} {}",
+            self.weekday_short(date.weekday()),
+            self.month_day(date)
+        )
+    }
+
+    /// Date and time, like Oct 18 at 1:15 PM or 18 oct a las 13:15.
+    pub fn date_time(self, date_time: OffsetDateTime) -> String {
+        let (date, time) = (
+            self.month_day(date_time.date()),
+            self.time(date_time.time()),
+        );
+        match self {
+            Lang::En => format!("{} at {}", date, time),
+            Lang::Es => format!("{} a las {}", date, time),
+            Lang::Pt => format!("{} às {}", date, time),
+        }
+    }
+
+    pub fn weekday_short(self, weekday: Weekday) -> &'static str {
+        let names = match self {
+            Lang::En => ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"],
+            Lang::Es => ["dom", "lun", "mar", "mié", "jue", "vie", "sáb"],
+            Lang::Pt => ["dom", "seg", "ter", "qua", "qui", "sex", "sáb"],
+        };
+        names[weekday.number_days_from_sunday() as usize]
+    }
+
+    pub fn month_short(self, month: Month) -> &'static str {
+        let names = match self {
+            Lang::En => [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+            ],
+            Lang::Es => [
+                "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+            ],
+            Lang::Pt => [
+                "jan", "fev", "mar", "abr", "mai", "jun", "jul", "ago", "set", "out", "nov", "dez",
+            ],
+        };
+        names[month as usize - 1]
+    }
+
+    pub fn month_long(self, month: Month) -> &'static str {
+        let names = match self {
+            Lang::En => [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ],
+            Lang::Es => [
+                "enero",
+                "febrero",
+                "marzo",
+                "abril",
+                "mayo",
+                "junio",
+                "julio",
+                "agosto",
+                "septiembre",
+                "octubre",
+                "noviembre",
+                "diciembre",
+            ],
+            Lang::Pt => [
+                "janeiro",
+                "fevereiro",
+                "março",
+                "abril",
+                "maio",
+                "junho",
+                "julho",
+                "agosto",
+                "setembro",
+                "outubro",
+                "novembro",
+                "dezembro",
+            ],
+        };
+        names[month as usize - 1]
+    }
+
+    /// Short legend label for open slots, like 5 open or 5-9 open. `last` is None for the highest step,
+    /// which has no upper bound.
+    pub fn open_range(self, first: u32, last: Option<u32>) -> String {
+        let range = match last {
+            Some(last) if last == first => first.to_string(),
+            Some(last) => format!("{}-{}", first, last),
+            None => format!("{}+", first),
+        };
+        match self {
+            Lang::En => format!("{} open", range),
+            Lang::Es => format!("{} libres", range),
+            Lang::Pt => format!("{} livres", range),
+        }
+    }
+
+    /// Like 1 open seat or 12 open seats.
+    pub fn open_seats(self, seats: u32) -> String {
+        match (self, seats) {
+            (Lang::En, 1) => "1 open seat".to_string(),
+            (Lang::En, _) => format!("{} open seats", seats),
+            (Lang::Es, 1) => "1 asiento libre".to_string(),
+            (Lang::Es, _) => format!("{} asientos libres", seats),
+            (Lang::Pt, 1) => "1 lugar livre".to_string(),
+            (Lang::Pt, _) => format!("{} lugares livres", seats),
+        }
+    }
+
+    /// Legend label for slots with room for the whole group.
+    pub fn room_for(self, size: u32) -> String {
+        match self {
+            Lang::En => format!("Room for {}", size),
+            Lang::Es => format!("Espacio para {}", size),
+            Lang::Pt => format!("Espaço para {}", size),
+        }
+    }
+
+    pub fn percent_full(self, percent: u32) -> String {
+        match self {
+            Lang::En => format!("{}% full", percent),
+            Lang::Es => format!("{}% lleno", percent),
+            Lang::Pt => format!("{}% ocupado", percent),
+        }
+    }
+
+    /// Month and year, like October 2026 or octubre de 2026.
+    pub fn month_year(self, date: Date) -> String {
+        match self {
+            Lang::En => format!("{} {}", self.month_long(date.month()), date.year()),
+            Lang::Es | Lang::Pt => format!("{} de {}", self.month_long(date.month()), date.year()),
+        }
+    }
+
+    pub fn week_of(self, start: Date) -> String {
+        match self {
+            Lang::En => format!("Week of {}", self.date(start)),
+            Lang::Es => format!("Semana del {}", self.date(start)),
+            Lang::Pt => format!("Semana de {}", self.date(start)),
+        }
+    }
+
+    pub fn date_range(self, first: Date, last: Date) -> String {
+        let (first, last) = (self.long_date(first), self.long_date(last));
+        match self {
+            Lang::En => format!("{} to {}", first, last),
+            Lang::Es => format!("Del {} al {}", first, last),
+            Lang::Pt => format!("De {} a {}", first, last),
+        }
+    }
+
+    pub fn times_shown_in(self, timezone: &str) -> String {
+        match self {
+            Lang::En => format!("Times shown in {}", timezone),
+            Lang::Es => format!("Horas en {}", timezone),
+            Lang::Pt => format!("Horários em {}", timezone),
+        }
+    }
+
+    /// Which of several pages a week was split into, like (1 of 2).
+    pub fn page_of(self, page: usize, pages: usize) -> String {
+        match self {
+            Lang::En => format!("({} of {})", page, pages),
+            Lang::Es | Lang::Pt => format!("({} de {})", page, pages),
+        }
+    }
+
+    pub fn fetched(self, date_time: OffsetDateTime, timezone: &str) -> String {
+        let when = self.date_time(date_time);
+        match self {
+            Lang::En => format!("Fetched {} {}", when, timezone),
+            Lang::Es => format!("Consultado el {} {}", when, timezone),
+            Lang::Pt => format!("Consultado em {} {}", when, timezone),
+        }
+    }
+
+    /// Totals for a schedule, like 295 open seats in 35 of 50 sessions (11 full, 4 closed).
+    pub fn session_totals(
+        self,
+        open_seats: u32,
+        open_sessions: usize,
+        sessions: usize,
+        full: usize,
+        closed: usize,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "{} open seats in {} of {} sessions ({} full, {} closed)",
+                open_seats, open_sessions, sessions, full, closed
+            ),
+            Lang::Es => format!(
+                "{} asientos libres en {} de {} sesiones ({} llenas, {} cerradas)",
+                open_seats, open_sessions, sessions, full, closed
+            ),
+            Lang::Pt => format!(
+                "{} lugares livres em {} de {} sessões ({} lotadas, {} fechadas)",
+                open_seats, open_sessions, sessions, full, closed
+            ),
+        }
+    }
+
+    pub fn most_open_seats_at(self, slot: Time) -> String {
+        match self {
+            Lang::En => format!("Most open seats at {}", self.time(slot)),
+            Lang::Es => format!("Más asientos libres a las {}", self.time(slot)),
+            Lang::Pt => format!("Mais lugares livres às {}", self.time(slot)),
+        }
+    }
+
+    pub fn fully_booked_days(self, days: &str) -> String {
+        match self {
+            Lang::En => format!("Fully booked: {}", days),
+            Lang::Es => format!("Completos: {}", days),
+            Lang::Pt => format!("Lotados: {}", days),
+        }
+    }
+
+    /// Key for the terminal colors, which only show one sample of the open seat shading.
+    pub fn darker_has_more(self) -> &'static str {
+        match self {
+            Lang::En => "open seats, darker has more",
+            Lang::Es => "asientos libres, más oscuro tiene más",
+            Lang::Pt => "lugares livres, mais escuro tem mais",
+        }
+    }
+
+    /// Details of a session besides its open seats, like "40 seats, 75% full, Spanish, special session".
+    pub fn session_details(
+        self,
+        total: Option<i32>,
+        fill: Option<u32>,
+        walk_in: Option<i32>,
+        language: Option<&str>,
+        special: bool,
+        closed: bool,
+    ) -> String {
+        let mut parts = vec![];
+        if let Some(total) = total {
+            parts.push(match self {
+                Lang::En => format!("{} seats", total),
+                Lang::Es => format!("{} asientos", total),
+                Lang::Pt => format!("{} lugares", total),
+            });
+        }
+        if let Some(fill) = fill {
+            parts.push(self.percent_full(fill));
+        }
+        if let Some(walk_in) = walk_in {
+            parts.push(match self {
+                Lang::En => format!("{} walk-in seats", walk_in.max(0)),
+                Lang::Es => format!("{} asientos sin cita", walk_in.max(0)),
+                Lang::Pt => format!("{} lugares sem agendamento", walk_in.max(0)),
+            });
+        }
+        if let Some(language) = language {
+            parts.push(language.to_string());
+        }
+        if special {
+            parts.push(self.labels().special_session.to_lowercase());
+        }
+        if closed {
+            parts.push(self.labels().closed.to_lowercase());
+        }
+
+        parts.join(", ")
+    }
+
+    /// Title of a calendar event for a session.
+    pub fn session_event(self, seats: u32) -> String {
+        let ordinance = self.ordinance(&OrdinanceType::Endowment);
+        match self {
+            Lang::En => format!("{} session - {}", ordinance, self.open_seats(seats)),
+            Lang::Es => format!("Sesión de {} - {}", ordinance, self.open_seats(seats)),
+            Lang::Pt => format!("Sessão de {} - {}", ordinance, self.open_seats(seats)),
+        }
+    }
+
+    /// Name for a calendar of a temple's sessions.
+    pub fn calendar_name(self, temple: &str) -> String {
+        match self {
+            Lang::En => format!("{} sessions", temple),
+            Lang::Es => format!("Sesiones de {}", temple),
+            Lang::Pt => format!("Sessões de {}", temple),
+        }
+    }
+
+    pub fn seats_as_of(self, seats: u32, fetched: OffsetDateTime) -> String {
+        let when = self.date_time(fetched);
+        match self {
+            Lang::En => format!("{} as of {} UTC.", self.open_seats(seats), when),
+            Lang::Es => format!("{} al {} UTC.", self.open_seats(seats), when),
+            Lang::Pt => format!("{} em {} UTC.", self.open_seats(seats), when),
+        }
+    }
+
+    pub fn change_kind(self, kind: ChangeKind) -> &'static str {
+        match (self, kind) {
+            (Lang::En, ChangeKind::Added) => "Added",
+            (Lang::En, ChangeKind::Removed) => "Removed",
+            (Lang::En, ChangeKind::BecameFull) => "Became full",
+            (Lang::En, ChangeKind::Closed) => "Closed",
+            (Lang::En, ChangeKind::Seats) => "Seats changed",
+            (Lang::Es, ChangeKind::Added) => "Nueva",
+            (Lang::Es, ChangeKind::Removed) => "Eliminada",
+            (Lang::Es, ChangeKind::BecameFull) => "Se llenó",
+            (Lang::Es, ChangeKind::Closed) => "Cerrada",
+            (Lang::Es, ChangeKind::Seats) => "Cambio de asientos",
+            (Lang::Pt, ChangeKind::Added) => "Nova",
+            (Lang::Pt, ChangeKind::Removed) => "Removida",
+            (Lang::Pt, ChangeKind::BecameFull) => "Lotou",
+            (Lang::Pt, ChangeKind::Closed) => "Fechada",
+            (Lang::Pt, ChangeKind::Seats) => "Mudança de lugares",
+        }
+    }
+
+    /// When the two snapshots being compared were saved.
+    pub fn compared(self, old: OffsetDateTime, new: OffsetDateTime, timezone: &str) -> String {
+        let (old, new) = (self.date_time(old), self.date_time(new));
+        match self {
+            Lang::En => format!("Compared {} with {} {}", old, new, timezone),
+            Lang::Es => format!("Comparación del {} con el {} {}", old, new, timezone),
+            Lang::Pt => format!("Comparação de {} com {} {}", old, new, timezone),
+        }
+    }
+
+    /// Totals for a diff, like 42 seats taken, 3 sessions became full, 1 added and 0 removed over 14 days.
+    pub fn diff_totals(
+        self,
+        seats_taken: u32,
+        became_full: usize,
+        added: usize,
+        removed: usize,
+        days: usize,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "{} seats taken, {} sessions became full, {} added and {} removed over {} days",
+                seats_taken, became_full, added, removed, days
+            ),
+            Lang::Es => format!(
+                "{} asientos ocupados, {} sesiones se llenaron, {} nuevas y {} eliminadas en {} días",
+                seats_taken, became_full, added, removed, days
+            ),
+            Lang::Pt => format!(
+                "{} lugares ocupados, {} sessões lotaram, {} novas e {} removidas em {} dias",
+                seats_taken, became_full, added, removed, days
+            ),
+        }
+    }
+
+    pub fn ordinance(self, ordinance: &OrdinanceType) -> &'static str {
+        match (self, ordinance) {
+            (Lang::En, OrdinanceType::Baptism) => "Baptism",
+            (Lang::En, OrdinanceType::Initiatory) => "Initiatory",
+            (Lang::En, OrdinanceType::Endowment) => "Endowment",
+            (Lang::En, OrdinanceType::Sealing) => "Sealing",
+            (Lang::Es, OrdinanceType::Baptism) => "Bautismo",
+            (Lang::Es, OrdinanceType::Initiatory) => "Iniciatoria",
+            (Lang::Es, OrdinanceType::Endowment) => "Investidura",
+            (Lang::Es, OrdinanceType::Sealing) => "Sellamiento",
+            (Lang::Pt, OrdinanceType::Baptism) => "Batismo",
+            (Lang::Pt, OrdinanceType::Initiatory) => "Iniciatória",
+            (Lang::Pt, OrdinanceType::Endowment) => "Investidura",
+            (Lang::Pt, OrdinanceType::Sealing) => "Selamento",
+        }
+    }
+
+    pub fn appointment_status(self, status: AppointmentStatus) -> &'static str {
+        match (self, status) {
+            (Lang::En, AppointmentStatus::Booked) => "Booked",
+            (Lang::En, AppointmentStatus::Cancelled) => "Cancelled",
+            (Lang::En, AppointmentStatus::Completed) => "Completed",
+            (Lang::Es, AppointmentStatus::Booked) => "Reservada",
+            (Lang::Es, AppointmentStatus::Cancelled) => "Cancelada",
+            (Lang::Es, AppointmentStatus::Completed) => "Completada",
+            (Lang::Pt, AppointmentStatus::Booked) => "Agendada",
+            (Lang::Pt, AppointmentStatus::Cancelled) => "Cancelada",
+            (Lang::Pt, AppointmentStatus::Completed) => "Concluída",
+            (Lang::En, AppointmentStatus::Unknown) => "Unknown status",
+            (Lang::Es, AppointmentStatus::Unknown) => "Estado desconocido",
+            (Lang::Pt, AppointmentStatus::Unknown) => "Situação desconhecida",
+        }
+    }
+
+    pub fn temple_status(self, status: &Status) -> &'static str {
+        match (self, status) {
+            (Lang::En, Status::Construction) => "Construction",
+            (Lang::En, Status::Operating) => "Operating",
+            (Lang::En, Status::Announced) => "Announced",
+            (Lang::En, Status::Renovation) => "Renovation",
+            (Lang::Es, Status::Construction) => "En construcción",
+            (Lang::Es, Status::Operating) => "En funcionamiento",
+            (Lang::Es, Status::Announced) => "Anunciado",
+            (Lang::Es, Status::Renovation) => "En renovación",
+            (Lang::Pt, Status::Construction) => "Em construção",
+            (Lang::Pt, Status::Operating) => "Em funcionamento",
+            (Lang::Pt, Status::Announced) => "Anunciado",
+            (Lang::Pt, Status::Renovation) => "Em reforma",
+        }
+    }
+
+    /// One line describing an appointment, like "Oct 4, 2026 at 9:00 AM MDT - Endowment at Provo Utah
+    /// Temple (Booked)".
+    pub fn appointment(
+        self,
+        date_time: OffsetDateTime,
+        timezone: &str,
+        ordinance: &str,
+        temple: &str,
+        status: AppointmentStatus,
+    ) -> String {
+        let (date, time) = (self.date(date_time.date()), self.time(date_time.time()));
+        let status = self.appointment_status(status);
+        match self {
+            Lang::En => format!(
+                "{} at {} {} - {} at {} ({})",
+                date, time, timezone, ordinance, temple, status
+            ),
+            Lang::Es => format!(
+                "{} a las {} {} - {} en {} ({})",
+                date, time, timezone, ordinance, temple, status
+            ),
+            Lang::Pt => format!(
+                "{} às {} {} - {} em {} ({})",
+                date, time, timezone, ordinance, temple, status
+            ),
+        }
+    }
+
+    /// Title of a calendar event for an appointment, like "Endowment at Provo Utah Temple".
+    pub fn appointment_event(self, ordinance: &str, temple: &str) -> String {
+        match self {
+            Lang::En => format!("{} at {}", ordinance, temple),
+            Lang::Es => format!("{} en {}", ordinance, temple),
+            Lang::Pt => format!("{} em {}", ordinance, temple),
+        }
+    }
+
+    /// Name for a calendar of a person's appointments.
+    pub fn appointments_calendar(self) -> &'static str {
+        match self {
+            Lang::En => "Temple appointments",
+            Lang::Es => "Citas en el templo",
+            Lang::Pt => "Agendamentos no templo",
+        }
+    }
+
+    pub fn confirmation(self, number: &str) -> String {
+        match self {
+            Lang::En => format!("confirmation #{}", number),
+            Lang::Es => format!("confirmación n.º {}", number),
+            Lang::Pt => format!("confirmação nº {}", number),
+        }
+    }
+
+    pub fn guests(self, count: u32) -> String {
+        match (self, count) {
+            (Lang::En, 1) => "1 guest".to_string(),
+            (Lang::En, _) => format!("{} guests", count),
+            (Lang::Es, 1) => "1 invitado".to_string(),
+            (Lang::Es, _) => format!("{} invitados", count),
+            (Lang::Pt, 1) => "1 convidado".to_string(),
+            (Lang::Pt, _) => format!("{} convidados", count),
+        }
+    }
+
+    /// How long until something starts, like "In 2 days, 3 hours and 5 minutes".
+    pub fn countdown(self, until: Duration) -> String {
+        let names = match self {
+            Lang::En => [("day", "days"), ("hour", "hours"), ("minute", "minutes")],
+            Lang::Es => [("día", "días"), ("hora", "horas"), ("minuto", "minutos")],
+            Lang::Pt => [("dia", "dias"), ("hora", "horas"), ("minuto", "minutos")],
+        };
+        let counts = [
+            until.whole_days(),
+            until.whole_hours() % 24,
+            until.whole_minutes() % 60,
+        ];
+        let parts: Vec<String> = counts
+            .iter()
+            .copied()
+            .zip(names.iter().copied())
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, (one, many))| {
+                format!("{} {}", count, if count == 1 { one } else { many })
+            })
+            .collect();
+
+        if parts.is_empty() {
+            return match self {
+                Lang::En => "Starting now",
+                Lang::Es => "Empieza ahora",
+                Lang::Pt => "Começa agora",
+            }
+            .to_string();
+        }
+
+        let and = match self {
+            Lang::En => "and",
+            Lang::Es => "y",
+            Lang::Pt => "e",
+        };
+        let list = match parts.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} {} {}", rest.join(", "), and, last),
+            None => unreachable!(),
+        };
+        match self {
+            Lang::En => format!("In {}", list),
+            Lang::Es => format!("En {}", list),
+            Lang::Pt => format!("Em {}", list),
+        }
+    }
+
+    pub fn visit_totals(self, appointments: u32, visits: u32) -> String {
+        match self {
+            Lang::En => format!(
+                "{} {} over {} {}",
+                appointments,
+                if appointments == 1 {
+                    "appointment"
+                } else {
+                    "appointments"
+                },
+                visits,
+                if visits == 1 { "visit" } else { "visits" }
+            ),
+            Lang::Es => format!(
+                "{} {} en {} {}",
+                appointments,
+                if appointments == 1 { "cita" } else { "citas" },
+                visits,
+                if visits == 1 { "visita" } else { "visitas" }
+            ),
+            Lang::Pt => format!(
+                "{} {} em {} {}",
+                appointments,
+                if appointments == 1 {
+                    "agendamento"
+                } else {
+                    "agendamentos"
+                },
+                visits,
+                if visits == 1 { "visita" } else { "visitas" }
+            ),
+        }
+    }
+
+    /// Months in a row with a visit, like "Longest streak: 3 months (Jan 2024 to Mar 2024)".
+    pub fn longest_streak(self, months: u32, start: Date, end: Date) -> String {
+        let (start, end) = (
+            format!("{} {}", self.month_short(start.month()), start.year()),
+            format!("{} {}", self.month_short(end.month()), end.year()),
+        );
+        match self {
+            Lang::En => format!(
+                "Longest streak: {} {} ({} to {})",
+                months,
+                if months == 1 { "month" } else { "months" },
+                start,
+                end
+            ),
+            Lang::Es => format!(
+                "Racha más larga: {} {} ({} a {})",
+                months,
+                if months == 1 { "mes" } else { "meses" },
+                start,
+                end
+            ),
+            Lang::Pt => format!(
+                "Maior sequência: {} {} ({} a {})",
+                months,
+                if months == 1 { "mês" } else { "meses" },
+                start,
+                end
+            ),
+        }
+    }
+
+    pub fn average_gap(self, days: f64) -> String {
+        match self {
+            Lang::En => format!("Average time between visits: {:.1} days", days),
+            Lang::Es => format!("Tiempo promedio entre visitas: {:.1} días", days),
+            Lang::Pt => format!("Tempo médio entre visitas: {:.1} dias", days),
+        }
+    }
+
+    /// Question asked before booking, like "Book Endowment at Provo Utah Temple on Oct 4, 2026 at 9:00
+    /// AM MDT for you and 2 guests?".
+    pub fn book_question(
+        self,
+        ordinance: &OrdinanceType,
+        temple: &str,
+        date_time: OffsetDateTime,
+        timezone: &str,
+        guests: u32,
+    ) -> String {
+        let (date, time) = (self.date(date_time.date()), self.time(date_time.time()));
+        let ordinance = self.ordinance(ordinance);
+        let who = match (self, guests) {
+            (Lang::En, 0) => "you".to_string(),
+            (Lang::En, _) => format!("you and {}", self.guests(guests)),
+            (Lang::Es, 0) => "usted".to_string(),
+            (Lang::Es, _) => format!("usted y {}", self.guests(guests)),
+            (Lang::Pt, 0) => "você".to_string(),
+            (Lang::Pt, _) => format!("você e {}", self.guests(guests)),
+        };
+        match self {
+            Lang::En => format!(
+                "Book {} at {} on {} at {} {} for {}?",
+                ordinance, temple, date, time, timezone, who
+            ),
+            Lang::Es => format!(
+                "¿Reservar {} en {} el {} a las {} {} para {}?",
+                ordinance, temple, date, time, timezone, who
+            ),
+            Lang::Pt => format!(
+                "Agendar {} em {} em {} às {} {} para {}?",
+                ordinance, temple, date, time, timezone, who
+            ),
+        }
+    }
+
+    pub fn cancel_question(self, appointment: &str) -> String {
+        match self {
+            Lang::En => format!("Cancel {}?", appointment),
+            Lang::Es => format!("¿Cancelar {}?", appointment),
+            Lang::Pt => format!("Cancelar {}?", appointment),
+        }
+    }
+
+    pub fn reschedule_question(
+        self,
+        appointment: &str,
+        date_time: OffsetDateTime,
+        timezone: &str,
+    ) -> String {
+        let (date, time) = (self.date(date_time.date()), self.time(date_time.time()));
+        match self {
+            Lang::En => format!("Move {} to {} at {} {}?", appointment, date, time, timezone),
+            Lang::Es => format!(
+                "¿Cambiar {} al {} a las {} {}?",
+                appointment, date, time, timezone
+            ),
+            Lang::Pt => format!(
+                "Remarcar {} para {} às {} {}?",
+                appointment, date, time, timezone
+            ),
+        }
+    }
+
+    /// Whether an answer to a yes or no question means yes, in English or this language.
+    pub fn is_yes(self, answer: &str) -> bool {
+        let answer = answer.trim().to_lowercase();
+        let yes = self.labels().yes.to_lowercase();
+        let first_letter = answer.chars().count() == 1 && yes.starts_with(&answer);
+        matches!(answer.as_str(), "y" | "yes") || answer == yes || first_letter
+    }
+
+    pub fn total_count(self, count: usize) -> String {
+        match self {
+            Lang::En => format!("Total Count: {}", count),
+            Lang::Es => format!("Total: {}", count),
+            Lang::Pt => format!("Total: {}", count),
+        }
+    }
+}
@@ -1,20 +1,26 @@
 use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
 
-use crate::network::get_temples;
 use anyhow::Result;
-use clap::{Parser, Subcommand, ValueEnum};
-use client::Client;
-use data::{FetchRange, Temple};
-use network::{get_appointments, get_schedules};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use temples::appointments::{self, AppointmentFilter, AppointmentSort, AppointmentStats};
+use temples::client::Client;
+use temples::data::{AppointmentJSON, AppointmentStatus, FetchRange, OrdinanceType, ZonedDateTime};
+use temples::locale::Lang;
+use temples::network::{
+    book_appointment, cancel_appointment, get_appointments, get_schedules, get_temples,
+    reschedule_appointment, BookingRequest,
+};
+use temples::snapshot::{ScheduleDiff, Snapshot};
+use temples::timezone::DisplayTimezone;
+use temples::visualize::{
+    self, AppointmentOutputFormat, Destination, DiffOutputFormat, FormatRegistry, HTMLWriter,
+    IcsWriter, Layout, Orientation, OutputOptions, PDFWriter, PageSize, Palette,
+};
 use term_table::row::Row;
 use term_table::table_cell::TableCell;
-use time::macros::format_description;
-use visualize::ScheduleOutputFormat;
-
-mod client;
-mod data;
-mod network;
-mod visualize;
+use time::{OffsetDateTime, PrimitiveDateTime};
 
 #[derive(Parser)]
 #[clap(version)]
@@ -25,6 +31,8 @@ struct Cli {
     command: Commands,
 }
 
+// Parsed once at startup, so the size of the schedule arguments doesn't matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Get list of temples
@@ -32,31 +40,266 @@ enum Commands {
         /// Which format to use for outputting the list of temples
         #[clap(short, long, value_enum, default_value_t = TempleOutputFormat::Table)]
         format: TempleOutputFormat,
+
+        /// Language for dates and labels in the table
+        #[clap(long, value_enum, default_value_t = Lang::En)]
+        lang: Lang,
     },
 
     /// Get existing temple appointments
-    Appointments {},
+    #[clap(args_conflicts_with_subcommands = true)]
+    Appointments {
+        #[clap(subcommand)]
+        command: Option<AppointmentCommands>,
+
+        #[clap(flatten)]
+        list: AppointmentArgs,
+    },
 
     /// Get a temple's endowment schedule
+    #[clap(args_conflicts_with_subcommands = true)]
     Schedules {
+        #[clap(subcommand)]
+        command: Option<ScheduleCommands>,
+
+        #[clap(flatten)]
+        fetch: Option<ScheduleArgs>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AppointmentCommands {
+    /// Summarize past appointments that weren't cancelled
+    Stats {
+        #[clap(flatten)]
+        filter: AppointmentFilter,
+
+        /// Which format to use for outputting the summary
+        #[clap(short, long, value_enum, default_value_t = StatsOutputFormat::Histogram)]
+        format: StatsOutputFormat,
+
+        /// Language for labels
+        #[clap(long, value_enum, default_value_t = Lang::En)]
+        lang: Lang,
+    },
+
+    /// Book an appointment. Experimental: only sends the booking with --experimental
+    Book {
         /// Temple id
+        #[clap(long)]
+        temple: u32,
+
+        /// Ordinance to book: baptism, initiatory, endowment or sealing
+        #[clap(long)]
+        ordinance: OrdinanceType,
+
+        /// When the appointment starts, in the temple's timezone, like 2024-01-31T09:00
+        #[clap(long, value_name = "DATETIME", value_parser = appointments::parse_date_time)]
+        at: PrimitiveDateTime,
+
+        /// People coming along besides you
+        #[clap(long, default_value_t = 0)]
+        guests: u32,
+
+        /// Show what would be booked without booking it
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Book without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Send the request to your real account even though it hasn't been verified against the API
+        #[clap(long)]
+        experimental: bool,
+
+        /// Language for dates, times and labels
+        #[clap(long, value_enum, default_value_t = Lang::En)]
+        lang: Lang,
+    },
+
+    /// Cancel an appointment. Experimental: only runs with --experimental
+    Cancel {
+        /// Confirmation number of the appointment
+        id: String,
+
+        /// Cancel without asking for confirmation
         #[clap(short, long)]
-        id: u32,
+        yes: bool,
+
+        /// Send the request to your real account even though it hasn't been verified against the API
+        #[clap(long)]
+        experimental: bool,
 
-        /// How many days to fetch. Use 0 to fetch from now until the end of the month.
-        #[clap(short, long, default_value_t = 0)]
-        count: u32,
+        /// Language for dates, times and labels
+        #[clap(long, value_enum, default_value_t = Lang::En)]
+        lang: Lang,
+    },
+
+    /// Move an appointment to another time, booking the new time before cancelling the old one.
+    /// Experimental: only runs with --experimental
+    Reschedule {
+        /// Confirmation number of the appointment
+        id: String,
+
+        /// When the new appointment starts, in the temple's timezone, like 2024-01-31T09:00
+        #[clap(long, value_name = "DATETIME", value_parser = appointments::parse_date_time)]
+        to: PrimitiveDateTime,
+
+        /// Reschedule without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
 
-        /// Format that schedule is output in
-        #[clap(short = 'o', long = "output", value_enum, default_value_t = ScheduleOutputFormat::Excel)]
-        format: ScheduleOutputFormat,
+        /// Send the request to your real account even though it hasn't been verified against the API
+        #[clap(long)]
+        experimental: bool,
 
-        /// The name of the output file
-        #[clap(short, long, default_value_t = String::from("schedule"))]
-        filename: String,
+        /// Language for dates, times and labels
+        #[clap(long, value_enum, default_value_t = Lang::En)]
+        lang: Lang,
     },
 }
 
+#[derive(Args)]
+struct AppointmentArgs {
+    /// Which format to use for outputting the appointments
+    #[clap(short, long, value_enum, default_value_t = AppointmentOutputFormat::Table)]
+    format: AppointmentOutputFormat,
+
+    /// Timezone to show times in: temple, local, or an IANA name like America/Denver. Calendar
+    /// events always use the temple's timezone.
+    #[clap(long, default_value = "temple")]
+    tz: DisplayTimezone,
+
+    /// Language for dates, times and labels
+    #[clap(long, value_enum, default_value_t = Lang::En)]
+    lang: Lang,
+
+    #[clap(flatten)]
+    filter: AppointmentFilter,
+
+    /// Order to list appointments in
+    #[clap(long, value_enum, default_value_t = AppointmentSort::Date)]
+    sort: AppointmentSort,
+
+    /// Only show the next upcoming appointment and how long until it starts
+    #[clap(long)]
+    next: bool,
+}
+
+#[derive(Subcommand)]
+enum ScheduleCommands {
+    /// Compare two schedule snapshots of the same temple
+    Diff {
+        /// Snapshot saved first
+        old: PathBuf,
+
+        /// Snapshot saved later
+        new: PathBuf,
+
+        /// Format that the comparison is output in
+        #[clap(short = 'o', long = "output", value_enum, default_value_t = DiffOutputFormat::Terminal)]
+        format: DiffOutputFormat,
+
+        /// File to write the comparison to, or - for stdout. Defaults to diff with the format's
+        /// extension, or stdout for terminal output. The extension is added if it's missing.
+        #[clap(short, long)]
+        filename: Option<String>,
+
+        /// Overwrite the output file if it already exists
+        #[clap(long)]
+        force: bool,
+
+        /// Timezone to show session times in: temple, local, or an IANA name like America/Denver
+        #[clap(long, default_value = "temple")]
+        tz: DisplayTimezone,
+
+        /// Language for dates, times and labels
+        #[clap(long, value_enum, default_value_t = Lang::En)]
+        lang: Lang,
+    },
+}
+
+#[derive(Args)]
+struct ScheduleArgs {
+    /// Temple id
+    #[clap(short, long)]
+    id: u32,
+
+    /// How many days to fetch. Use 0 to fetch from now until the end of the month.
+    #[clap(short, long, default_value_t = 0)]
+    count: u32,
+
+    #[clap(short = 'o', long = "output", default_value = "excel", help = output_help())]
+    format: String,
+
+    /// File to write the schedule to, or - for stdout. Defaults to schedule with the format's
+    /// extension, or stdout for terminal output. The extension is added if it's missing.
+    #[clap(short, long)]
+    filename: Option<String>,
+
+    /// Overwrite the output file or snapshot if it already exists
+    #[clap(long)]
+    force: bool,
+
+    /// Timezone to show session times in: temple, local, or an IANA name like America/Denver
+    #[clap(long, default_value = "temple")]
+    tz: DisplayTimezone,
+
+    /// How to arrange the days in HTML, PDF and Excel output
+    #[clap(long, value_enum, default_value_t = Layout::Grid)]
+    layout: Layout,
+
+    /// Minutes covered by each row of the schedule. Defaults to the largest size, at least 15 minutes, that gives every session its own row.
+    #[clap(long, value_parser = clap::value_parser!(u16).range(1..=240))]
+    slot_minutes: Option<u16>,
+
+    /// Paper size for PDF output
+    #[clap(long, value_enum, default_value_t = PageSize::Letter)]
+    page_size: PageSize,
+
+    /// Page orientation for PDF output
+    #[clap(long, value_enum, default_value_t = Orientation::Landscape)]
+    orientation: Orientation,
+
+    /// Only put sessions with at least this many open seats in ICS output
+    #[clap(long, default_value_t = 1)]
+    min_seats: u32,
+
+    /// Colors used for open and full slots
+    #[clap(long, value_enum, default_value_t = Palette::Classic)]
+    palette: Palette,
+
+    /// Comma separated seat counts where the color steps up, like 5,10,20. Defaults to a smooth gradient.
+    #[clap(long, value_delimiter = ',')]
+    thresholds: Vec<u32>,
+
+    /// Highlight slots with a session that has room for a group this big
+    #[clap(long)]
+    group_size: Option<u32>,
+
+    /// Template file to render the HTML output with, in place of the built in one
+    #[clap(long, value_name = "FILE")]
+    template: Option<PathBuf>,
+
+    /// Language for dates, times and labels. CSV and JSON output stay in machine readable formats.
+    #[clap(long, value_enum, default_value_t = Lang::En)]
+    lang: Lang,
+
+    /// Also save the fetched schedule to this file, to compare with a later fetch using `schedules diff`
+    #[clap(long, value_name = "FILE")]
+    snapshot: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone)]
+enum StatsOutputFormat {
+    /// Totals and histograms
+    Histogram,
+
+    /// Full information in JSON
+    Json,
+}
+
 #[derive(ValueEnum, Clone)]
 enum TempleOutputFormat {
     /// ASCII table
@@ -76,7 +319,7 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Temples { format } => {
+        Commands::Temples { format, lang } => {
             let temples = get_temples()?;
             match format {
                 TempleOutputFormat::Table => {
@@ -84,35 +327,30 @@ fn main() -> Result<()> {
                     table.max_column_width = 80;
                     table.style = term_table::TableStyle::extended();
 
+                    let labels = lang.labels();
                     table.add_row(Row::new([
-                        TableCell::new("Name"),
-                        TableCell::new("Status"),
-                        TableCell::new("Dedicated"),
-                        TableCell::new("Id"),
+                        TableCell::new(labels.name),
+                        TableCell::new(labels.status),
+                        TableCell::new(labels.dedicated),
+                        TableCell::new(labels.id),
                     ]));
 
-                    let date_format =
-                        format_description!("[month repr:short] [day padding:none], [year]");
-
                     let count = temples.len();
                     for temple in temples {
                         table.add_row(Row::new([
                             TableCell::new(temple.name),
-                            TableCell::new(format!("{:?}", temple.status)),
+                            TableCell::new(lang.temple_status(&temple.status)),
                             TableCell::new(
                                 temple
                                     .date
-                                    .and_then(|d| d.format(&date_format).ok())
+                                    .map(|d| lang.date(d))
                                     .unwrap_or_else(|| "".to_string()),
                             ),
                             TableCell::new(temple.temple_org_id),
                         ]));
                     }
 
-                    table.add_row(Row::new([TableCell::new(format!(
-                        "Total Count: {}",
-                        count
-                    ))]));
+                    table.add_row(Row::new([TableCell::new(lang.total_count(count))]));
 
                     println!("{}", table.render());
                 }
@@ -144,20 +382,292 @@ fn main() -> Result<()> {
                 }
             }
         }
-        Commands::Appointments {} => {
+        Commands::Appointments {
+            command:
+                Some(AppointmentCommands::Stats {
+                    filter,
+                    format,
+                    lang,
+                }),
+            ..
+        } => {
+            let client = Client::new()?;
+            let temples = get_temples()?;
+
+            let now = OffsetDateTime::now_utc();
+            let appointments = filter.apply(
+                get_appointments(&client, &temples)?,
+                AppointmentSort::Date,
+                now,
+            );
+            let stats = AppointmentStats::new(&appointments, now);
+
+            match format {
+                StatsOutputFormat::Histogram => print_stats(&stats, lang),
+                StatsOutputFormat::Json => {
+                    println!("{}", serde_json::ser::to_string_pretty(&stats)?)
+                }
+            }
+        }
+        Commands::Appointments {
+            command:
+                Some(AppointmentCommands::Book {
+                    temple,
+                    ordinance,
+                    at,
+                    guests,
+                    dry_run,
+                    yes,
+                    experimental,
+                    lang,
+                }),
+            ..
+        } => {
+            let temples = get_temples()?;
+            let temple = temples
+                .into_iter()
+                .find(|t| t.temple_org_id == temple)
+                .ok_or_else(|| anyhow::anyhow!("Invalid temple id: {}", temple))?;
+
+            let start = ZonedDateTime::from_local(at, temple.timezone());
+            if start.local() < OffsetDateTime::now_utc() {
+                anyhow::bail!("Can't book an appointment that has already started");
+            }
+
+            let request = BookingRequest {
+                temple: &temple,
+                ordinance,
+                at,
+                guests,
+            };
+            let labels = lang.labels();
+
+            if dry_run {
+                println!("{}", labels.dry_run);
+                println!("{}", serde_json::ser::to_string_pretty(&request.body())?);
+                return Ok(());
+            }
+            require_experimental(experimental, "book")?;
+
+            let question = lang.book_question(
+                &ordinance,
+                &temple.name,
+                start.local(),
+                &start.abbreviation(),
+                guests,
+            );
+            if !yes && !confirm(&question, lang)? {
+                println!("{}", labels.not_booked);
+                return Ok(());
+            }
+
+            let client = Client::new()?;
+            let appointment = book_appointment(&client, &request)?;
+            println!("{}", labels.booked);
+            println!("{}", appointment.display_in(DisplayTimezone::Temple, lang));
+        }
+        Commands::Appointments {
+            command:
+                Some(AppointmentCommands::Cancel {
+                    id,
+                    yes,
+                    experimental,
+                    lang,
+                }),
+            ..
+        } => {
+            require_experimental(experimental, "cancel")?;
+            let client = Client::new()?;
+            let temples = get_temples()?;
+            let appointments = get_appointments(&client, &temples)?;
+            let appointment = find_appointment(&appointments, &id)?;
+            let labels = lang.labels();
+
+            let question = lang.cancel_question(
+                &appointment
+                    .display_in(DisplayTimezone::Temple, lang)
+                    .to_string(),
+            );
+            if !yes && !confirm(&question, lang)? {
+                println!("{}", labels.not_cancelled);
+                return Ok(());
+            }
+
+            cancel_appointment(&client, &id)?;
+            println!("{}", labels.cancelled_appointment);
+            println!("{}", appointment.display_in(DisplayTimezone::Temple, lang));
+        }
+        Commands::Appointments {
+            command:
+                Some(AppointmentCommands::Reschedule {
+                    id,
+                    to,
+                    yes,
+                    experimental,
+                    lang,
+                }),
+            ..
+        } => {
+            require_experimental(experimental, "reschedule")?;
             let client = Client::new()?;
+            let temples = get_temples()?;
+            let appointments = get_appointments(&client, &temples)?;
+            let appointment = find_appointment(&appointments, &id)?;
+            let temple = temples
+                .iter()
+                .find(|t| Some(t.temple_org_id) == appointment.temple_org_id)
+                .ok_or_else(|| anyhow::anyhow!("Unknown temple for appointment {}", id))?;
+            let request = BookingRequest {
+                temple,
+                ordinance: appointment.ordinance_type().ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Can't reschedule appointment {}: unknown appointment type {}",
+                        id,
+                        appointment.ordinance_name(lang)
+                    )
+                })?,
+                at: to,
+                guests: appointment.guest_count(),
+            };
+
+            let start = request.start();
+            if start.local() < OffsetDateTime::now_utc() {
+                anyhow::bail!("Can't move an appointment to a time that has already started");
+            }
 
-            let appointments = get_appointments(&client)?;
-            for appointment in appointments {
-                println!("{appointment}");
+            let labels = lang.labels();
+            let question = lang.reschedule_question(
+                &appointment
+                    .display_in(DisplayTimezone::Temple, lang)
+                    .to_string(),
+                start.local(),
+                &start.abbreviation(),
+            );
+            if !yes && !confirm(&question, lang)? {
+                println!("{}", labels.not_rescheduled);
+                return Ok(());
             }
+
+            let booked = reschedule_appointment(&client, &request, &id)?;
+            println!("{}", labels.rescheduled);
+            println!("{}", booked.display_in(DisplayTimezone::Temple, lang));
+        }
+        Commands::Appointments {
+            command: None,
+            list:
+                AppointmentArgs {
+                    format,
+                    tz,
+                    lang,
+                    filter,
+                    sort,
+                    next,
+                },
+        } => {
+            let client = Client::new()?;
+            let temples = get_temples()?;
+
+            let now = OffsetDateTime::now_utc();
+            let appointments = filter.apply(get_appointments(&client, &temples)?, sort, now);
+
+            if next {
+                match appointments::next(&appointments, now) {
+                    Some(appointment) => {
+                        println!("{}", appointment.display_in(tz, lang));
+                        println!("{}", lang.countdown(appointment.date_time().local() - now));
+                    }
+                    None => println!("{}", lang.labels().no_upcoming_appointments),
+                }
+                return Ok(());
+            }
+
+            let mut output = std::io::stdout().lock();
+            visualize::write_appointments(&appointments, format, &mut output, tz, lang)?;
+        }
+        Commands::Schedules {
+            command:
+                Some(ScheduleCommands::Diff {
+                    old,
+                    new,
+                    format,
+                    filename,
+                    force,
+                    tz,
+                    lang,
+                }),
+            ..
+        } => {
+            let default_name = match format {
+                DiffOutputFormat::Terminal => None,
+                _ => Some("diff"),
+            };
+            let destination =
+                Destination::new(filename.as_deref(), default_name, format.extension());
+            destination.check(force)?;
+
+            let diff = ScheduleDiff::new(&Snapshot::load(&old)?, &Snapshot::load(&new)?)?;
+            let mut output = destination.create(force)?;
+            visualize::write_diff(&diff, format, &mut output, tz, lang)?;
+            output.flush()?;
         }
         Commands::Schedules {
-            id,
-            count,
-            format,
-            filename,
+            fetch:
+                Some(ScheduleArgs {
+                    id,
+                    count,
+                    format,
+                    filename,
+                    tz,
+                    layout,
+                    slot_minutes,
+                    page_size,
+                    orientation,
+                    min_seats,
+                    palette,
+                    thresholds,
+                    group_size,
+                    template,
+                    lang,
+                    force,
+                    snapshot,
+                }),
+            ..
         } => {
+            // Built in writers with the options for this run.
+            let mut formats = FormatRegistry::default();
+            formats
+                .register("html", HTMLWriter { template })
+                .register(
+                    "pdf",
+                    PDFWriter {
+                        page_size,
+                        orientation,
+                    },
+                )
+                .register("ics", IcsWriter { min_seats });
+            let writer = formats.get(&format).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown output format {}. Use one of {}",
+                    format,
+                    formats.names().collect::<Vec<_>>().join(", ")
+                )
+            })?;
+
+            // The terminal writer is for reading right away, so it goes to stdout unless a file is named.
+            let default_name = match format.as_str() {
+                "terminal" => None,
+                _ => Some("schedule"),
+            };
+            let destination =
+                Destination::new(filename.as_deref(), default_name, writer.extension());
+            let snapshot = snapshot.map(Destination::File);
+
+            // Check before fetching so a schedule isn't fetched only to be thrown away.
+            destination.check(force)?;
+            if let Some(snapshot) = &snapshot {
+                snapshot.check(force)?;
+            }
+
             let range = if count == 0 {
                 FetchRange::ThisMonthFromToday
             } else {
@@ -171,30 +681,140 @@ fn main() -> Result<()> {
                 .find(|t| t.temple_org_id == id)
                 .ok_or_else(|| anyhow::anyhow!("Invalid temple id: {}", id))?;
 
-            let schedules = get_schedules(&client, range, &temple)?;
+            let schedules = get_schedules(&client, range, &temple, OrdinanceType::Endowment)?;
+            if let Some(snapshot) = snapshot {
+                let mut output = snapshot.create(force)?;
+                Snapshot::new(&temple, &schedules).save(&mut output)?;
+                output.flush()?;
+            }
 
-            visualize::write_output(&schedules, &temple, format, &filename)?;
+            let options = OutputOptions {
+                timezone: tz,
+                layout,
+                slot_minutes,
+                palette,
+                thresholds,
+                group_size,
+                lang,
+                interactive: destination.is_terminal(),
+            };
+            let mut output = destination.create(force)?;
+            visualize::write_output(&schedules, &temple, writer, &mut output, &options)?;
+            output.flush()?;
+        }
+        Commands::Schedules { .. } => {
+            unreachable!("clap requires a temple id without a subcommand")
         }
     }
 
     Ok(())
 }
 
-fn print_histogram<K>(histogram: &HashMap<K, u32>)
+/// Help for the schedule output option, listing the formats in the default registry.
+fn output_help() -> String {
+    let formats = FormatRegistry::default();
+    format!(
+        "Format that schedule is output in: {}",
+        formats.names().collect::<Vec<_>>().join(", ")
+    )
+}
+
+/// Booking, cancelling and rescheduling send requests that haven't been checked against the real API,
+/// so they only touch a person's account when asked for explicitly.
+fn require_experimental(experimental: bool, command: &str) -> Result<()> {
+    if !experimental {
+        anyhow::bail!(
+            "`appointments {}` is experimental: its requests haven't been verified against the real API, \
+             so it could fail or change your appointments in unexpected ways. Pass --experimental to run it anyway.",
+            command
+        );
+    }
+    Ok(())
+}
+
+/// Ask a yes or no question on the terminal. Anything but yes counts as no.
+fn confirm(question: &str, lang: Lang) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(lang.is_yes(&answer))
+}
+
+/// The appointment with this confirmation number, if it can still be changed.
+fn find_appointment<'a>(
+    appointments: &'a [AppointmentJSON],
+    id: &str,
+) -> Result<&'a AppointmentJSON> {
+    let appointment = appointments
+        .iter()
+        .find(|a| a.confirmation_number.as_deref() == Some(id))
+        .ok_or_else(|| anyhow::anyhow!("No appointment with confirmation number {}", id))?;
+
+    if appointment.appointment_status == AppointmentStatus::Cancelled {
+        anyhow::bail!("Appointment {} is already cancelled", id);
+    }
+    if appointment.date_time().local() < OffsetDateTime::now_utc() {
+        anyhow::bail!("Appointment {} has already started", id);
+    }
+    Ok(appointment)
+}
+
+fn print_histogram<'a, K>(histogram: impl IntoIterator<Item = (&'a K, &'a u32)>)
 where
-    K: std::cmp::Ord + std::fmt::Display + std::hash::Hash,
+    K: std::cmp::Ord + std::fmt::Display + 'a,
 {
-    let mut keys: Vec<_> = histogram.keys().collect();
-    keys.sort();
+    let mut entries: Vec<_> = histogram.into_iter().collect();
+    entries.sort();
 
-    let widths = keys.iter().map(|k| k.to_string().len());
+    let widths = entries.iter().map(|(k, _)| k.to_string().len());
     let max_width = widths.max().unwrap_or(0).min(20);
 
-    for key in keys {
+    for (key, count) in entries {
         print!("{:width$.prec$} ", key, width = max_width, prec = max_width);
-        for _ in 0..histogram[key] {
+        for _ in 0..*count {
             print!("*");
         }
         println!();
     }
 }
+
+fn print_stats(stats: &AppointmentStats, lang: Lang) {
+    let labels = lang.labels();
+    println!("{}", lang.visit_totals(stats.appointments, stats.visits));
+    if let Some(streak) = &stats.longest_streak {
+        println!(
+            "{}",
+            lang.longest_streak(streak.months, streak.start, streak.end)
+        );
+    }
+    if let Some(days) = stats.average_gap_days {
+        println!("{}", lang.average_gap(days));
+    }
+
+    let by_ordinance: HashMap<&str, u32> = stats
+        .by_ordinance
+        .iter()
+        .map(|(ordinance, count)| (lang.ordinance(ordinance), *count))
+        .collect();
+    if stats.appointments == 0 {
+        return;
+    }
+
+    println!();
+    println!("{}", labels.by_ordinance);
+    print_histogram(&by_ordinance);
+
+    println!();
+    println!("{}", labels.by_temple);
+    print_histogram(&stats.by_temple);
+
+    println!();
+    println!("{}", labels.by_month);
+    print_histogram(&stats.by_month);
+
+    println!();
+    println!("{}", labels.by_year);
+    print_histogram(&stats.by_year);
+}
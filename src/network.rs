@@ -1,47 +1,82 @@
-use anyhow::Result;
-use time::{OffsetDateTime, PrimitiveDateTime, Time};
+use anyhow::{Context, Result};
+use time::{macros::format_description, OffsetDateTime, PrimitiveDateTime};
 
 use crate::{
     client::Client,
-    data::{AppointmentJSON, Day, FetchRange, SessionsJSON, Temple},
+    data::{
+        AppointmentJSON, Availability, Day, FetchRange, OrdinanceType, SessionsJSON, Temple,
+        ZonedDateTime,
+    },
+    timezone,
 };
 
-pub fn get_appointments(client: &Client) -> Result<Vec<AppointmentJSON>> {
+/// An appointment to book. The time is the wall clock time at the temple.
+pub struct BookingRequest<'a> {
+    pub temple: &'a Temple,
+    pub ordinance: OrdinanceType,
+    pub at: PrimitiveDateTime,
+    pub guests: u32,
+}
+
+pub fn get_appointments(client: &Client, temples: &[Temple]) -> Result<Vec<AppointmentJSON>> {
     // Fetch appointments.
-    let appointments: Vec<AppointmentJSON> =
-        ureq::get("https://tos.churchofjesuschrist.org/api/appointments")
-            .set("Cookie", &client.cookie)
-            .call()?
-            .into_json()?;
+    let mut appointments: Vec<AppointmentJSON> = ureq::get(&client.url("appointments"))
+        .set("Cookie", &client.cookie)
+        .call()?
+        .into_json()?;
+
+    // The API only gives us the temple id, so look up the rest from the temple list.
+    for appointment in &mut appointments {
+        if let Some(temple) = temples
+            .iter()
+            .find(|t| Some(t.temple_org_id) == appointment.temple_org_id)
+        {
+            appointment.set_temple(temple);
+        }
+    }
 
     Ok(appointments)
 }
 
-pub fn get_schedules(client: &Client, range: FetchRange, temple: &Temple) -> Result<Vec<Day>> {
-    let now = OffsetDateTime::now_local().expect("Unable to get local time");
+pub fn get_schedules(
+    client: &Client,
+    range: FetchRange,
+    temple: &Temple,
+    ordinance: OrdinanceType,
+) -> Result<Vec<Day>> {
+    // "Today" is whatever day it currently is at the temple.
+    let timezone = temple.timezone();
+    let now = timezone::now_in(timezone);
 
     // Fetch schedules for the rest of the month.
     let mut num_days_fetched = 0;
     let mut days = vec![];
-    let mut next_date = now.date();
+    let mut next_date = match range {
+        FetchRange::Day(date) => date,
+        _ => now.date(),
+    };
     'fetch_loop: loop {
-        let sessions: SessionsJSON =
-            ureq::post("https://tos.churchofjesuschrist.org/api/templeSchedule/getSessionInfo")
-                .set("Cookie", &client.cookie)
-                .send_json(ureq::json!({
-                    "sessionYear":next_date.year(),
-                    "sessionMonth":next_date.month() as u8 - 1,
-                    "sessionDay":next_date.day(),
-                    "appointmentType":"PROXY_ENDOWMENT",
-                    "templeOrgId":temple.temple_org_id
-                }))?
-                .into_json()?;
+        let sessions: SessionsJSON = ureq::post(&client.url("templeSchedule/getSessionInfo"))
+            .set("Cookie", &client.cookie)
+            .send_json(ureq::json!({
+                "sessionYear":next_date.year(),
+                "sessionMonth":next_date.month() as u8 - 1,
+                "sessionDay":next_date.day(),
+                "appointmentType":ordinance.appointment_type(),
+                "templeOrgId":temple.temple_org_id
+            }))?
+            .into_json()?;
+        let fetched_at = OffsetDateTime::now_utc();
 
         num_days_fetched += 1;
 
         days.push(Day {
-            date: PrimitiveDateTime::new(next_date, Time::MIDNIGHT).assume_offset(now.offset()),
-            sessions,
+            date: next_date,
+            sessions: sessions
+                .session_list
+                .into_iter()
+                .map(|s| s.into_session(timezone, fetched_at))
+                .collect(),
         });
 
         match next_date.next_day() {
@@ -57,6 +92,7 @@ pub fn get_schedules(client: &Client, range: FetchRange, temple: &Temple) -> Res
                             break 'fetch_loop;
                         }
                     }
+                    FetchRange::Day(_) => break 'fetch_loop,
                 }
 
                 next_date = next;
@@ -68,6 +104,139 @@ pub fn get_schedules(client: &Client, range: FetchRange, temple: &Temple) -> Res
     Ok(days)
 }
 
+impl BookingRequest<'_> {
+    /// When the appointment starts.
+    pub fn start(&self) -> ZonedDateTime {
+        ZonedDateTime::from_local(self.at, self.temple.timezone())
+    }
+
+    /// What gets sent to book the appointment.
+    ///
+    /// This hasn't been checked against what the appointment page sends when booking. The temple,
+    /// appointment type and date fields are the ones the page sends to getSessionInfo (see
+    /// `Client::new`), including the zero based month, so they should line up with what booking
+    /// expects. The names of `sessionTime` and `numberOfGuests` are guesses, the second based on how
+    /// fetched appointments spell it.
+    pub fn body(&self) -> serde_json::Value {
+        let time_format = format_description!("[hour]:[minute]");
+        ureq::json!({
+            "templeOrgId": self.temple.temple_org_id,
+            "appointmentType": self.ordinance.appointment_type(),
+            "sessionYear": self.at.year(),
+            "sessionMonth": self.at.month() as u8 - 1,
+            "sessionDay": self.at.day(),
+            "sessionTime": self.at.format(&time_format).unwrap(),
+            "numberOfGuests": self.guests,
+        })
+    }
+}
+
+/// Book an appointment, returning it as the API confirmed it.
+pub fn book_appointment(client: &Client, request: &BookingRequest) -> Result<AppointmentJSON> {
+    let response = match ureq::post(&client.url("appointments"))
+        .set("Cookie", &client.cookie)
+        .send_json(request.body())
+    {
+        Ok(response) => response,
+        Err(e) => return Err(refused("Booking", e)),
+    };
+
+    let mut appointment: AppointmentJSON = response
+        .into_json()
+        .context("Unexpected response to booking")?;
+    appointment.set_temple(request.temple);
+    Ok(appointment)
+}
+
+/// Cancel the appointment with this confirmation number.
+///
+/// Like booking, this hasn't been checked against the appointment page. The endpoint is a guess based
+/// on how appointments are fetched.
+pub fn cancel_appointment(client: &Client, confirmation_number: &str) -> Result<()> {
+    let path = format!("appointments/{}", confirmation_number);
+    match ureq::delete(&client.url(&path))
+        .set("Cookie", &client.cookie)
+        .call()
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(refused("Cancelling", e)),
+    }
+}
+
+/// Make sure the session being booked has a seat for the patron and each of their guests.
+pub fn check_seats(client: &Client, request: &BookingRequest) -> Result<()> {
+    let start = request.start();
+    let when = format!(
+        "{} {}",
+        request
+            .at
+            .format(format_description!("[year]-[month]-[day] [hour]:[minute]"))?,
+        start.abbreviation()
+    );
+
+    let days = get_schedules(
+        client,
+        FetchRange::Day(request.at.date()),
+        request.temple,
+        request.ordinance,
+    )?;
+    let session = days
+        .iter()
+        .flat_map(|d| &d.sessions)
+        .find(|s| s.time.local() == start.local())
+        .ok_or_else(|| anyhow::anyhow!("{} has no session at {}", request.temple.name, when))?;
+
+    match session.details.availability() {
+        Availability::Open(seats) if seats > request.guests => Ok(()),
+        Availability::Open(seats) => anyhow::bail!(
+            "Only {} seats are left at {}, but {} are needed",
+            seats,
+            when,
+            request.guests + 1
+        ),
+        Availability::Full | Availability::Closed => {
+            anyhow::bail!("The session at {} can't be booked", when)
+        }
+    }
+}
+
+/// Move an appointment by checking the new time has room, booking it and then cancelling the old
+/// appointment, so the old one is only given up once the new one is certain. Returns the new appointment.
+pub fn reschedule_appointment(
+    client: &Client,
+    request: &BookingRequest,
+    confirmation_number: &str,
+) -> Result<AppointmentJSON> {
+    let unchanged = || {
+        format!(
+            "Couldn't book the new time, so appointment {} is unchanged",
+            confirmation_number
+        )
+    };
+    check_seats(client, request).with_context(unchanged)?;
+    let booked = book_appointment(client, request).with_context(unchanged)?;
+
+    if let Err(e) = cancel_appointment(client, confirmation_number) {
+        return Err(e.context(format!(
+            "Booked the new appointment ({}), but cancelling appointment {} failed. You now have both, so cancel the old one with `appointments cancel {}`",
+            booked, confirmation_number, confirmation_number
+        )));
+    }
+
+    Ok(booked)
+}
+
+/// Include what the API said when it turned a request down, since it usually explains why.
+fn refused(action: &str, error: ureq::Error) -> anyhow::Error {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let message = response.into_string().unwrap_or_default();
+            anyhow::anyhow!("{} was refused ({}): {}", action, code, message.trim())
+        }
+        e => e.into(),
+    }
+}
+
 pub fn get_temples() -> Result<Vec<Temple>> {
     const START_DELIMITER: &str = "templeList\":";
     const END_DELIMITER: &str = "}]";
@@ -88,3 +257,313 @@ pub fn get_temples() -> Result<Vec<Temple>> {
     let temples: Vec<Temple> = serde_json::de::from_str(json_string)?;
     Ok(temples)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{tests::temple, AppointmentStatus};
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc::{channel, Receiver},
+    };
+    use time::macros::datetime;
+
+    /// A request the mock server got.
+    struct Request {
+        method: String,
+        path: String,
+        body: String,
+    }
+
+    /// Start a server that answers requests with the given statuses and bodies, in order, and a client
+    /// that sends its requests there. Every request is passed on through the receiver.
+    fn mock(responses: Vec<(u16, &'static str)>) -> (Client, Receiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = Client {
+            cookie: "session=test".to_string(),
+            api_url: format!("http://{}/api", listener.local_addr().unwrap()),
+        };
+        let (requests, received) = channel();
+
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap().to_string();
+                let path = parts.next().unwrap().to_string();
+
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut request_body = vec![0; length];
+                reader.read_exact(&mut request_body).unwrap();
+
+                requests
+                    .send(Request {
+                        method,
+                        path,
+                        body: String::from_utf8(request_body).unwrap(),
+                    })
+                    .unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (client, received)
+    }
+
+    const BOOKED: &str = r#"{
+        "appointmentType": "PROXY_SEALING",
+        "appointmentDateTime": "2030-11-04T17:00:00Z",
+        "appointmentTime": "09:30",
+        "templeOrgId": 99,
+        "appointmentStatus": "BOOKED",
+        "confirmationNumber": "NEW42",
+        "numberOfGuests": 2
+    }"#;
+
+    #[test]
+    fn books_an_appointment() {
+        let (client, requests) = mock(vec![(200, BOOKED)]);
+        let temple = temple(99, "Provo Utah Temple");
+        let request = BookingRequest {
+            temple: &temple,
+            ordinance: OrdinanceType::Sealing,
+            at: datetime!(2030-11-04 09:30),
+            guests: 2,
+        };
+
+        let appointment = book_appointment(&client, &request).unwrap();
+
+        let sent = requests.recv().unwrap();
+        assert_eq!(sent.method, "POST");
+        assert_eq!(sent.path, "/api/appointments");
+        let body: serde_json::Value = serde_json::from_str(&sent.body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "templeOrgId": 99,
+                "appointmentType": "PROXY_SEALING",
+                "sessionYear": 2030,
+                "sessionMonth": 10,
+                "sessionDay": 4,
+                "sessionTime": "09:30",
+                "numberOfGuests": 2,
+            })
+        );
+
+        assert_eq!(appointment.confirmation_number.as_deref(), Some("NEW42"));
+        assert_eq!(appointment.ordinance_type(), Some(OrdinanceType::Sealing));
+        assert_eq!(appointment.appointment_status, AppointmentStatus::Booked);
+        assert_eq!(appointment.guest_count(), 2);
+
+        // The temple fills in what the API leaves out, so the time is in the temple's timezone.
+        assert_eq!(appointment.temple_display_name(), "Provo Utah Temple");
+        let time = appointment.date_time();
+        assert_eq!(time.timezone(), temple.timezone());
+        assert_eq!(time.local().time(), time::macros::time!(9:30));
+    }
+
+    #[test]
+    fn refused_bookings_include_the_reason() {
+        let (client, _requests) = mock(vec![(409, r#"{"message":"Session is full"}"#)]);
+        let temple = temple(99, "Provo Utah Temple");
+        let request = BookingRequest {
+            temple: &temple,
+            ordinance: OrdinanceType::Endowment,
+            at: datetime!(2030-11-04 09:00),
+            guests: 0,
+        };
+
+        let error = book_appointment(&client, &request).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"Booking was refused (409): {"message":"Session is full"}"#
+        );
+    }
+
+    /// Seats left at the time of `sealing_request`, enough for the patron and both guests.
+    const OPEN: &str = r#"{"sessionList":[{"time":"2030-11-04T09:30:00","details":{"remainingOnlineSeatsAvailable":3}}]}"#;
+
+    fn sealing_request(temple: &Temple) -> BookingRequest<'_> {
+        BookingRequest {
+            temple,
+            ordinance: OrdinanceType::Sealing,
+            at: datetime!(2030-11-04 09:30),
+            guests: 2,
+        }
+    }
+
+    #[test]
+    fn failed_booking_keeps_the_old_appointment() {
+        let (client, requests) = mock(vec![(200, OPEN), (409, r#"{"message":"Session is full"}"#)]);
+        let temple = temple(99, "Provo Utah Temple");
+
+        let error = reschedule_appointment(&client, &sealing_request(&temple), "OLD1").unwrap_err();
+
+        assert!(format!("{:#}", error).contains("appointment OLD1 is unchanged"));
+        assert_eq!(
+            requests.recv().unwrap().path,
+            "/api/templeSchedule/getSessionInfo"
+        );
+        assert_eq!(requests.recv().unwrap().path, "/api/appointments");
+        assert!(requests.try_recv().is_err(), "nothing should be cancelled");
+    }
+
+    #[test]
+    fn failed_cancel_names_both_appointments() {
+        let (client, requests) = mock(vec![(200, OPEN), (200, BOOKED), (500, "Try again later")]);
+        let temple = temple(99, "Provo Utah Temple");
+
+        let error = reschedule_appointment(&client, &sealing_request(&temple), "OLD1").unwrap_err();
+
+        let message = format!("{:#}", error);
+        assert!(message.contains("NEW42"), "{}", message);
+        assert!(
+            message.contains("cancelling appointment OLD1 failed"),
+            "{}",
+            message
+        );
+        assert!(message.contains("You now have both"), "{}", message);
+        assert!(
+            message.contains("Cancelling was refused (500)"),
+            "{}",
+            message
+        );
+
+        assert_eq!(
+            requests.recv().unwrap().path,
+            "/api/templeSchedule/getSessionInfo"
+        );
+        assert_eq!(requests.recv().unwrap().path, "/api/appointments");
+        let cancel = requests.recv().unwrap();
+        assert_eq!(cancel.method, "DELETE");
+        assert_eq!(cancel.path, "/api/appointments/OLD1");
+    }
+
+    #[test]
+    fn reschedules_by_booking_then_cancelling() {
+        let (client, requests) = mock(vec![(200, OPEN), (200, BOOKED), (200, "{}")]);
+        let temple = temple(99, "Provo Utah Temple");
+
+        let booked = reschedule_appointment(&client, &sealing_request(&temple), "OLD1").unwrap();
+        assert_eq!(booked.confirmation_number.as_deref(), Some("NEW42"));
+
+        let sent: Vec<(String, String)> = requests
+            .try_iter()
+            .map(|request| (request.method, request.path))
+            .collect();
+        assert_eq!(
+            sent,
+            [
+                (
+                    "POST".to_string(),
+                    "/api/templeSchedule/getSessionInfo".to_string()
+                ),
+                ("POST".to_string(), "/api/appointments".to_string()),
+                ("DELETE".to_string(), "/api/appointments/OLD1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn full_sessions_are_not_rescheduled_into() {
+        let (client, requests) = mock(vec![(
+            200,
+            r#"{"sessionList":[{"time":"2030-11-04T09:30:00","details":{"remainingOnlineSeatsAvailable":0}}]}"#,
+        )]);
+        let temple = temple(99, "Provo Utah Temple");
+
+        let error = reschedule_appointment(&client, &sealing_request(&temple), "OLD1").unwrap_err();
+
+        let message = format!("{:#}", error);
+        assert!(
+            message.contains("appointment OLD1 is unchanged"),
+            "{}",
+            message
+        );
+        assert!(message.contains("can't be booked"), "{}", message);
+        assert_eq!(
+            requests.recv().unwrap().path,
+            "/api/templeSchedule/getSessionInfo"
+        );
+        assert!(
+            requests.try_recv().is_err(),
+            "nothing should be booked or cancelled"
+        );
+    }
+
+    #[test]
+    fn checks_there_are_enough_seats() {
+        let (client, requests) = mock(vec![
+            (
+                200,
+                r#"{"sessionList":[{"time":"2030-11-04T09:30:00","details":{"remainingOnlineSeatsAvailable":3}}]}"#,
+            ),
+            (
+                200,
+                r#"{"sessionList":[{"time":"2030-11-04T09:30:00","details":{"remainingOnlineSeatsAvailable":2}}]}"#,
+            ),
+            (
+                200,
+                r#"{"sessionList":[{"time":"2030-11-04T09:30:00","details":{"remainingOnlineSeatsAvailable":0}}]}"#,
+            ),
+            (
+                200,
+                r#"{"sessionList":[{"time":"2030-11-04T09:30:00","details":{"remainingOnlineSeatsAvailable":9,"closed":true}}]}"#,
+            ),
+            (
+                200,
+                r#"{"sessionList":[{"time":"2030-11-04T10:00:00","details":{"remainingOnlineSeatsAvailable":9}}]}"#,
+            ),
+        ]);
+        let temple = temple(99, "Provo Utah Temple");
+        let request = sealing_request(&temple);
+
+        // Room for the patron and both guests.
+        check_seats(&client, &request).unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(sent["appointmentType"], "PROXY_SEALING");
+        assert_eq!(sent["sessionDay"], 4);
+
+        let error = check_seats(&client, &request).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "Only 2 seats are left at 2030-11-04 09:30 MST, but 3 are needed"
+        );
+
+        let error = check_seats(&client, &request).unwrap_err().to_string();
+        assert_eq!(error, "The session at 2030-11-04 09:30 MST can't be booked");
+
+        let error = check_seats(&client, &request).unwrap_err().to_string();
+        assert_eq!(error, "The session at 2030-11-04 09:30 MST can't be booked");
+
+        let error = check_seats(&client, &request).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "Provo Utah Temple has no session at 2030-11-04 09:30 MST"
+        );
+    }
+}
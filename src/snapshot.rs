@@ -0,0 +1,339 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Write},
+    path::Path,
+};
+use time::{serde::rfc3339, Date, OffsetDateTime};
+
+use crate::data::{Availability, Day, Session, SessionDetails, Temple, ZonedDateTime};
+
+/// A fetched schedule saved to disk, so it can be compared with a later fetch of the same temple.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub temple: Temple,
+
+    #[serde(with = "rfc3339")]
+    pub saved_at: OffsetDateTime,
+
+    days: Vec<SnapshotDay>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotDay {
+    date: Date,
+    sessions: Vec<SnapshotSession>,
+}
+
+/// Sessions are saved with their offset instead of their timezone, which comes back from the temple
+/// when the snapshot is loaded.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotSession {
+    #[serde(with = "rfc3339")]
+    time: OffsetDateTime,
+
+    details: SessionDetails,
+
+    #[serde(with = "rfc3339")]
+    fetched_at: OffsetDateTime,
+}
+
+/// What happened to a session between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    BecameFull,
+    Closed,
+
+    /// Still open or reopened, with a different number of open seats.
+    Seats,
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionChange {
+    pub time: ZonedDateTime,
+
+    /// None if the session is new.
+    pub old: Option<Availability>,
+
+    /// None if the session was removed.
+    pub new: Option<Availability>,
+}
+
+/// Every session that changed between two snapshots of a temple's schedule, earliest first.
+pub struct ScheduleDiff {
+    pub temple: Temple,
+    pub old_saved_at: OffsetDateTime,
+    pub new_saved_at: OffsetDateTime,
+
+    /// Days in both snapshots. Sessions on other days aren't compared, since a later fetch usually
+    /// starts on a later day.
+    pub days_compared: usize,
+
+    pub changes: Vec<SessionChange>,
+}
+
+impl Snapshot {
+    pub fn new(temple: &Temple, days: &[Day]) -> Self {
+        Self {
+            temple: temple.clone(),
+            saved_at: OffsetDateTime::now_utc(),
+            days: days
+                .iter()
+                .map(|day| SnapshotDay {
+                    date: day.date,
+                    sessions: day
+                        .sessions
+                        .iter()
+                        .map(|s| SnapshotSession {
+                            time: s.time.local(),
+                            details: s.details.clone(),
+                            fetched_at: s.fetched_at,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn save(&self, output: &mut dyn Write) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *output, self)?;
+        writeln!(output)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Unable to read snapshot {}", path.display()))?;
+        serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Invalid snapshot {}", path.display()))
+    }
+
+    /// The saved days, with session times back in the temple's timezone.
+    pub fn days(&self) -> Vec<Day> {
+        let timezone = self.temple.timezone();
+        self.days
+            .iter()
+            .map(|day| Day {
+                date: day.date,
+                sessions: day
+                    .sessions
+                    .iter()
+                    .map(|s| Session {
+                        time: ZonedDateTime::from_offset(s.time, timezone),
+                        details: s.details.clone(),
+                        fetched_at: s.fetched_at,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+impl ScheduleDiff {
+    /// Compare two snapshots of the same temple. Sessions are matched by when they start.
+    pub fn new(old: &Snapshot, new: &Snapshot) -> Result<Self> {
+        if old.temple.temple_org_id != new.temple.temple_org_id {
+            anyhow::bail!(
+                "Snapshots are of different temples: {} and {}",
+                old.temple.name,
+                new.temple.name
+            );
+        }
+
+        let (old_days, new_days) = (old.days(), new.days());
+        let dates: Vec<Date> = old_days
+            .iter()
+            .map(|d| d.date)
+            .filter(|date| new_days.iter().any(|d| d.date == *date))
+            .collect();
+
+        let sessions = |days: &[Day]| -> BTreeMap<i64, Session> {
+            days.iter()
+                .filter(|d| dates.contains(&d.date))
+                .flat_map(|d| &d.sessions)
+                .map(|s| (s.time.local().unix_timestamp(), s.clone()))
+                .collect()
+        };
+        let (mut before, after) = (sessions(&old_days), sessions(&new_days));
+
+        let mut changes = vec![];
+        for (start, session) in &after {
+            let old = before.remove(start).map(|s| s.details.availability());
+            let new = Some(session.details.availability());
+            if old != new {
+                changes.push(SessionChange {
+                    time: session.time,
+                    old,
+                    new,
+                });
+            }
+        }
+        for session in before.values() {
+            changes.push(SessionChange {
+                time: session.time,
+                old: Some(session.details.availability()),
+                new: None,
+            });
+        }
+        changes.sort_by_key(|c| c.time.local());
+
+        Ok(Self {
+            temple: new.temple.clone(),
+            old_saved_at: old.saved_at,
+            new_saved_at: new.saved_at,
+            days_compared: dates.len(),
+            changes,
+        })
+    }
+
+    /// Open seats that were taken in sessions found in both snapshots, not counting ones that freed up.
+    pub fn seats_taken(&self) -> u32 {
+        self.changes
+            .iter()
+            .filter_map(|c| c.seat_change())
+            .filter(|change| *change < 0)
+            .map(|change| change.unsigned_abs() as u32)
+            .sum()
+    }
+
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind() == kind).count()
+    }
+}
+
+impl SessionChange {
+    pub fn kind(&self) -> ChangeKind {
+        match (self.old, self.new) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            (Some(Availability::Open(_)), Some(Availability::Full)) => ChangeKind::BecameFull,
+            (_, Some(Availability::Closed)) => ChangeKind::Closed,
+            _ => ChangeKind::Seats,
+        }
+    }
+
+    /// Difference in open seats, if the session is in both snapshots.
+    pub fn seat_change(&self) -> Option<i64> {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => Some(open_seats(new) as i64 - open_seats(old) as i64),
+            _ => None,
+        }
+    }
+}
+
+pub fn open_seats(availability: Availability) -> u32 {
+    match availability {
+        Availability::Open(remaining) => remaining,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{day, temple};
+    use time::{
+        macros::{date, time},
+        Time,
+    };
+
+    #[test]
+    fn classifies_changes() {
+        let temple = temple(99, "Provo Utah Temple");
+        let (first, second, third) = (
+            date!(2024 - 03 - 05),
+            date!(2024 - 03 - 06),
+            date!(2024 - 03 - 07),
+        );
+        let old = Snapshot::new(
+            &temple,
+            &[
+                day(
+                    &temple,
+                    first,
+                    &[
+                        (time!(9:00), Some(5)),
+                        (time!(10:00), Some(2)),
+                        (time!(11:00), Some(3)),
+                        (time!(12:00), Some(4)),
+                        (time!(13:00), Some(0)),
+                        (time!(15:00), Some(1)),
+                    ],
+                ),
+                day(&temple, second, &[(time!(9:00), Some(1))]),
+            ],
+        );
+        let new = Snapshot::new(
+            &temple,
+            &[
+                day(
+                    &temple,
+                    first,
+                    &[
+                        (time!(9:00), Some(3)),
+                        (time!(10:00), Some(0)),
+                        (time!(11:00), None),
+                        (time!(13:00), Some(2)),
+                        (time!(14:00), Some(6)),
+                        (time!(15:00), Some(1)),
+                    ],
+                ),
+                day(&temple, third, &[(time!(9:00), Some(8))]),
+            ],
+        );
+
+        let diff = ScheduleDiff::new(&old, &new).unwrap();
+
+        // Only the first day is in both, so the sessions on the others aren't added or removed.
+        assert_eq!(diff.days_compared, 1);
+        let changes: Vec<(Time, ChangeKind)> = diff
+            .changes
+            .iter()
+            .map(|c| (c.time.local().time(), c.kind()))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (time!(9:00), ChangeKind::Seats),
+                (time!(10:00), ChangeKind::BecameFull),
+                (time!(11:00), ChangeKind::Closed),
+                (time!(12:00), ChangeKind::Removed),
+                (time!(13:00), ChangeKind::Seats),
+                (time!(14:00), ChangeKind::Added),
+            ]
+        );
+        assert_eq!(diff.count(ChangeKind::Seats), 2);
+
+        // Seats freed up at 13:00 don't make up for the ones taken elsewhere.
+        assert_eq!(diff.seats_taken(), 2 + 2 + 3);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let temple = temple(99, "Provo Utah Temple");
+        let date = date!(2024 - 03 - 05);
+        let snapshot = Snapshot::new(&temple, &[day(&temple, date, &[(time!(9:00), Some(5))])]);
+
+        let mut saved = vec![];
+        snapshot.save(&mut saved).unwrap();
+        let loaded: Snapshot = serde_json::from_slice(&saved).unwrap();
+
+        let days = loaded.days();
+        assert_eq!(days[0].date, date);
+        assert_eq!(days[0].sessions[0].time.local().time(), time!(9:00));
+        assert_eq!(days[0].sessions[0].time.timezone(), temple.timezone());
+    }
+
+    #[test]
+    fn refuses_different_temples() {
+        let old = Snapshot::new(&temple(99, "Provo Utah Temple"), &[]);
+        let new = Snapshot::new(&temple(1234, "Oakland California Temple"), &[]);
+        assert!(ScheduleDiff::new(&old, &new).is_err());
+    }
+}
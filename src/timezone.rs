@@ -0,0 +1,489 @@
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Mutex;
+use time::OffsetDateTime;
+use time_tz::{timezones, OffsetDateTimeExt, Tz};
+
+// Temple timezones are derived from the country, state / region and city listed for each temple on the
+// temple list page. The tables below are bundled with the binary so we never need to call out to a
+// geolocation or timezone service. Most countries only have a single timezone, so only the larger ones
+// need a region (and occasionally a city) to pick the right zone.
+
+const DEFAULT_TIMEZONE: &str = "UTC";
+
+/// Countries that use a single timezone for all of their temples.
+const COUNTRY_TIMEZONES: &[(&str, &str)] = &[
+    ("Albania", "Europe/Tirane"),
+    ("American Samoa", "Pacific/Pago_Pago"),
+    ("Angola", "Africa/Luanda"),
+    ("Argentina", "America/Argentina/Buenos_Aires"),
+    ("Armenia", "Asia/Yerevan"),
+    ("Australia", "Australia/Sydney"),
+    ("Austria", "Europe/Vienna"),
+    ("Bahamas", "America/Nassau"),
+    ("Barbados", "America/Barbados"),
+    ("Belgium", "Europe/Brussels"),
+    ("Belize", "America/Belize"),
+    ("Benin", "Africa/Porto-Novo"),
+    ("Bolivia", "America/La_Paz"),
+    ("Botswana", "Africa/Gaborone"),
+    ("Brazil", "America/Sao_Paulo"),
+    ("Bulgaria", "Europe/Sofia"),
+    ("Burundi", "Africa/Bujumbura"),
+    ("Cambodia", "Asia/Phnom_Penh"),
+    ("Cameroon", "Africa/Douala"),
+    ("Canada", "America/Toronto"),
+    ("Cape Verde", "Atlantic/Cape_Verde"),
+    ("Chile", "America/Santiago"),
+    ("China", "Asia/Shanghai"),
+    ("Colombia", "America/Bogota"),
+    ("Costa Rica", "America/Costa_Rica"),
+    ("Cote d'Ivoire", "Africa/Abidjan"),
+    ("Croatia", "Europe/Zagreb"),
+    ("Czech Republic", "Europe/Prague"),
+    ("Democratic Republic of the Congo", "Africa/Kinshasa"),
+    ("Denmark", "Europe/Copenhagen"),
+    ("Dominican Republic", "America/Santo_Domingo"),
+    ("Ecuador", "America/Guayaquil"),
+    ("El Salvador", "America/El_Salvador"),
+    ("England", "Europe/London"),
+    ("Estonia", "Europe/Tallinn"),
+    ("Eswatini", "Africa/Mbabane"),
+    ("Ethiopia", "Africa/Addis_Ababa"),
+    ("Federated States of Micronesia", "Pacific/Pohnpei"),
+    ("Fiji", "Pacific/Fiji"),
+    ("Finland", "Europe/Helsinki"),
+    ("France", "Europe/Paris"),
+    ("French Polynesia", "Pacific/Tahiti"),
+    ("Gabon", "Africa/Libreville"),
+    ("Germany", "Europe/Berlin"),
+    ("Ghana", "Africa/Accra"),
+    ("Greece", "Europe/Athens"),
+    ("Guam", "Pacific/Guam"),
+    ("Guatemala", "America/Guatemala"),
+    ("Guyana", "America/Guyana"),
+    ("Haiti", "America/Port-au-Prince"),
+    ("Honduras", "America/Tegucigalpa"),
+    ("Hong Kong", "Asia/Hong_Kong"),
+    ("Hungary", "Europe/Budapest"),
+    ("Iceland", "Atlantic/Reykjavik"),
+    ("India", "Asia/Kolkata"),
+    ("Indonesia", "Asia/Jakarta"),
+    ("Ireland", "Europe/Dublin"),
+    ("Italy", "Europe/Rome"),
+    ("Ivory Coast", "Africa/Abidjan"),
+    ("Jamaica", "America/Jamaica"),
+    ("Japan", "Asia/Tokyo"),
+    ("Kazakhstan", "Asia/Almaty"),
+    ("Kenya", "Africa/Nairobi"),
+    ("Kiribati", "Pacific/Tarawa"),
+    ("Latvia", "Europe/Riga"),
+    ("Lesotho", "Africa/Maseru"),
+    ("Liberia", "Africa/Monrovia"),
+    ("Lithuania", "Europe/Vilnius"),
+    ("Madagascar", "Indian/Antananarivo"),
+    ("Malawi", "Africa/Blantyre"),
+    ("Malaysia", "Asia/Kuala_Lumpur"),
+    ("Marshall Islands", "Pacific/Majuro"),
+    ("Mexico", "America/Mexico_City"),
+    ("Micronesia", "Pacific/Pohnpei"),
+    ("Mongolia", "Asia/Ulaanbaatar"),
+    ("Mozambique", "Africa/Maputo"),
+    ("Namibia", "Africa/Windhoek"),
+    ("Netherlands", "Europe/Amsterdam"),
+    ("New Caledonia", "Pacific/Noumea"),
+    ("New Zealand", "Pacific/Auckland"),
+    ("Nicaragua", "America/Managua"),
+    ("Nigeria", "Africa/Lagos"),
+    ("Northern Ireland", "Europe/London"),
+    ("Norway", "Europe/Oslo"),
+    ("Pakistan", "Asia/Karachi"),
+    ("Panama", "America/Panama"),
+    ("Papua New Guinea", "Pacific/Port_Moresby"),
+    ("Paraguay", "America/Asuncion"),
+    ("Peru", "America/Lima"),
+    ("Philippines", "Asia/Manila"),
+    ("Poland", "Europe/Warsaw"),
+    ("Portugal", "Europe/Lisbon"),
+    ("Puerto Rico", "America/Puerto_Rico"),
+    ("Republic of the Congo", "Africa/Brazzaville"),
+    ("Romania", "Europe/Bucharest"),
+    ("Russia", "Europe/Moscow"),
+    ("Rwanda", "Africa/Kigali"),
+    ("Samoa", "Pacific/Apia"),
+    ("Scotland", "Europe/London"),
+    ("Senegal", "Africa/Dakar"),
+    ("Sierra Leone", "Africa/Freetown"),
+    ("Singapore", "Asia/Singapore"),
+    ("Solomon Islands", "Pacific/Guadalcanal"),
+    ("South Africa", "Africa/Johannesburg"),
+    ("South Korea", "Asia/Seoul"),
+    ("Spain", "Europe/Madrid"),
+    ("Sri Lanka", "Asia/Colombo"),
+    ("Suriname", "America/Paramaribo"),
+    ("Sweden", "Europe/Stockholm"),
+    ("Switzerland", "Europe/Zurich"),
+    ("Taiwan", "Asia/Taipei"),
+    ("Tanzania", "Africa/Dar_es_Salaam"),
+    ("Thailand", "Asia/Bangkok"),
+    ("Togo", "Africa/Lome"),
+    ("Tonga", "Pacific/Tongatapu"),
+    ("Trinidad and Tobago", "America/Port_of_Spain"),
+    ("Uganda", "Africa/Kampala"),
+    ("Ukraine", "Europe/Kyiv"),
+    ("United Arab Emirates", "Asia/Dubai"),
+    ("United Kingdom", "Europe/London"),
+    ("United States", "America/Denver"),
+    ("Uruguay", "America/Montevideo"),
+    ("Vanuatu", "Pacific/Efate"),
+    ("Venezuela", "America/Caracas"),
+    ("Vietnam", "Asia/Ho_Chi_Minh"),
+    ("Wales", "Europe/London"),
+    ("Zambia", "Africa/Lusaka"),
+    ("Zimbabwe", "Africa/Harare"),
+];
+
+/// Regions of countries that span more than one timezone. Every region of these countries is listed, so
+/// a region that isn't found is one we don't know about rather than one that uses the country's timezone.
+const REGION_TIMEZONES: &[(&str, &str, &str)] = &[
+    (
+        "Australia",
+        "Australian Capital Territory",
+        "Australia/Sydney",
+    ),
+    ("Australia", "New South Wales", "Australia/Sydney"),
+    ("Australia", "Northern Territory", "Australia/Darwin"),
+    ("Australia", "Queensland", "Australia/Brisbane"),
+    ("Australia", "South Australia", "Australia/Adelaide"),
+    ("Australia", "Tasmania", "Australia/Hobart"),
+    ("Australia", "Victoria", "Australia/Melbourne"),
+    ("Australia", "Western Australia", "Australia/Perth"),
+    ("Brazil", "Acre", "America/Rio_Branco"),
+    ("Brazil", "Alagoas", "America/Maceio"),
+    ("Brazil", "Amapa", "America/Belem"),
+    ("Brazil", "Amazonas", "America/Manaus"),
+    ("Brazil", "Bahia", "America/Bahia"),
+    ("Brazil", "Ceara", "America/Fortaleza"),
+    ("Brazil", "Distrito Federal", "America/Sao_Paulo"),
+    ("Brazil", "Espirito Santo", "America/Sao_Paulo"),
+    ("Brazil", "Goias", "America/Sao_Paulo"),
+    ("Brazil", "Maranhao", "America/Fortaleza"),
+    ("Brazil", "Mato Grosso", "America/Cuiaba"),
+    ("Brazil", "Mato Grosso do Sul", "America/Campo_Grande"),
+    ("Brazil", "Minas Gerais", "America/Sao_Paulo"),
+    ("Brazil", "Para", "America/Belem"),
+    ("Brazil", "Paraiba", "America/Fortaleza"),
+    ("Brazil", "Parana", "America/Sao_Paulo"),
+    ("Brazil", "Pernambuco", "America/Recife"),
+    ("Brazil", "Piaui", "America/Fortaleza"),
+    ("Brazil", "Rio de Janeiro", "America/Sao_Paulo"),
+    ("Brazil", "Rio Grande do Norte", "America/Fortaleza"),
+    ("Brazil", "Rio Grande do Sul", "America/Sao_Paulo"),
+    ("Brazil", "Rondonia", "America/Porto_Velho"),
+    ("Brazil", "Roraima", "America/Boa_Vista"),
+    ("Brazil", "Santa Catarina", "America/Sao_Paulo"),
+    ("Brazil", "Sao Paulo", "America/Sao_Paulo"),
+    ("Brazil", "Sergipe", "America/Maceio"),
+    ("Brazil", "Tocantins", "America/Araguaina"),
+    ("Canada", "Alberta", "America/Edmonton"),
+    ("Canada", "British Columbia", "America/Vancouver"),
+    ("Canada", "Manitoba", "America/Winnipeg"),
+    ("Canada", "New Brunswick", "America/Moncton"),
+    ("Canada", "Newfoundland and Labrador", "America/St_Johns"),
+    ("Canada", "Northwest Territories", "America/Edmonton"),
+    ("Canada", "Nova Scotia", "America/Halifax"),
+    ("Canada", "Nunavut", "America/Iqaluit"),
+    ("Canada", "Ontario", "America/Toronto"),
+    ("Canada", "Prince Edward Island", "America/Halifax"),
+    ("Canada", "Quebec", "America/Toronto"),
+    ("Canada", "Saskatchewan", "America/Regina"),
+    ("Canada", "Yukon", "America/Whitehorse"),
+    ("Mexico", "Aguascalientes", "America/Mexico_City"),
+    ("Mexico", "Baja California", "America/Tijuana"),
+    ("Mexico", "Baja California Sur", "America/Mazatlan"),
+    ("Mexico", "Campeche", "America/Mexico_City"),
+    ("Mexico", "Chiapas", "America/Mexico_City"),
+    ("Mexico", "Chihuahua", "America/Chihuahua"),
+    ("Mexico", "Ciudad de Mexico", "America/Mexico_City"),
+    ("Mexico", "Coahuila", "America/Mexico_City"),
+    ("Mexico", "Colima", "America/Mexico_City"),
+    ("Mexico", "Distrito Federal", "America/Mexico_City"),
+    ("Mexico", "Durango", "America/Mexico_City"),
+    ("Mexico", "Estado de Mexico", "America/Mexico_City"),
+    ("Mexico", "Guanajuato", "America/Mexico_City"),
+    ("Mexico", "Guerrero", "America/Mexico_City"),
+    ("Mexico", "Hidalgo", "America/Mexico_City"),
+    ("Mexico", "Jalisco", "America/Mexico_City"),
+    ("Mexico", "Mexico", "America/Mexico_City"),
+    ("Mexico", "Michoacan", "America/Mexico_City"),
+    ("Mexico", "Morelos", "America/Mexico_City"),
+    ("Mexico", "Nayarit", "America/Mazatlan"),
+    ("Mexico", "Nuevo Leon", "America/Mexico_City"),
+    ("Mexico", "Oaxaca", "America/Mexico_City"),
+    ("Mexico", "Puebla", "America/Mexico_City"),
+    ("Mexico", "Queretaro", "America/Mexico_City"),
+    ("Mexico", "Quintana Roo", "America/Cancun"),
+    ("Mexico", "San Luis Potosi", "America/Mexico_City"),
+    ("Mexico", "Sinaloa", "America/Mazatlan"),
+    ("Mexico", "Sonora", "America/Hermosillo"),
+    ("Mexico", "Tabasco", "America/Mexico_City"),
+    ("Mexico", "Tamaulipas", "America/Mexico_City"),
+    ("Mexico", "Tlaxcala", "America/Mexico_City"),
+    ("Mexico", "Veracruz", "America/Mexico_City"),
+    ("Mexico", "Yucatan", "America/Mexico_City"),
+    ("Mexico", "Zacatecas", "America/Mexico_City"),
+    ("United States", "Alabama", "America/Chicago"),
+    ("United States", "Alaska", "America/Anchorage"),
+    ("United States", "Arizona", "America/Phoenix"),
+    ("United States", "Arkansas", "America/Chicago"),
+    ("United States", "California", "America/Los_Angeles"),
+    ("United States", "Colorado", "America/Denver"),
+    ("United States", "Connecticut", "America/New_York"),
+    ("United States", "Delaware", "America/New_York"),
+    ("United States", "District of Columbia", "America/New_York"),
+    ("United States", "Florida", "America/New_York"),
+    ("United States", "Georgia", "America/New_York"),
+    ("United States", "Guam", "Pacific/Guam"),
+    ("United States", "Hawaii", "Pacific/Honolulu"),
+    ("United States", "Idaho", "America/Boise"),
+    ("United States", "Illinois", "America/Chicago"),
+    ("United States", "Indiana", "America/Indiana/Indianapolis"),
+    ("United States", "Iowa", "America/Chicago"),
+    ("United States", "Kansas", "America/Chicago"),
+    ("United States", "Kentucky", "America/Kentucky/Louisville"),
+    ("United States", "Louisiana", "America/Chicago"),
+    ("United States", "Maine", "America/New_York"),
+    ("United States", "Maryland", "America/New_York"),
+    ("United States", "Massachusetts", "America/New_York"),
+    ("United States", "Michigan", "America/Detroit"),
+    ("United States", "Minnesota", "America/Chicago"),
+    ("United States", "Mississippi", "America/Chicago"),
+    ("United States", "Missouri", "America/Chicago"),
+    ("United States", "Montana", "America/Denver"),
+    ("United States", "Nebraska", "America/Chicago"),
+    ("United States", "Nevada", "America/Los_Angeles"),
+    ("United States", "New Hampshire", "America/New_York"),
+    ("United States", "New Jersey", "America/New_York"),
+    ("United States", "New Mexico", "America/Denver"),
+    ("United States", "New York", "America/New_York"),
+    ("United States", "North Carolina", "America/New_York"),
+    ("United States", "North Dakota", "America/Chicago"),
+    ("United States", "Ohio", "America/New_York"),
+    ("United States", "Oklahoma", "America/Chicago"),
+    ("United States", "Oregon", "America/Los_Angeles"),
+    ("United States", "Pennsylvania", "America/New_York"),
+    ("United States", "Puerto Rico", "America/Puerto_Rico"),
+    ("United States", "Rhode Island", "America/New_York"),
+    ("United States", "South Carolina", "America/New_York"),
+    ("United States", "South Dakota", "America/Chicago"),
+    ("United States", "Tennessee", "America/Chicago"),
+    ("United States", "Texas", "America/Chicago"),
+    ("United States", "Utah", "America/Denver"),
+    ("United States", "Vermont", "America/New_York"),
+    ("United States", "Virginia", "America/New_York"),
+    ("United States", "Washington", "America/Los_Angeles"),
+    ("United States", "West Virginia", "America/New_York"),
+    ("United States", "Wisconsin", "America/Chicago"),
+    ("United States", "Wyoming", "America/Denver"),
+];
+
+/// Cities that don't share a timezone with the rest of their region.
+const CITY_TIMEZONES: &[(&str, &str, &str, &str)] = &[
+    (
+        "Democratic Republic of the Congo",
+        "",
+        "Kananga",
+        "Africa/Lubumbashi",
+    ),
+    (
+        "Democratic Republic of the Congo",
+        "",
+        "Lubumbashi",
+        "Africa/Lubumbashi",
+    ),
+    (
+        "Democratic Republic of the Congo",
+        "",
+        "Mbuji-Mayi",
+        "Africa/Lubumbashi",
+    ),
+    ("Mexico", "Chihuahua", "Ciudad Juarez", "America/Denver"),
+    ("United States", "Florida", "Pensacola", "America/Chicago"),
+    (
+        "United States",
+        "Idaho",
+        "Coeur d'Alene",
+        "America/Los_Angeles",
+    ),
+    (
+        "United States",
+        "South Dakota",
+        "Rapid City",
+        "America/Denver",
+    ),
+    (
+        "United States",
+        "Tennessee",
+        "Knoxville",
+        "America/New_York",
+    ),
+    ("United States", "Texas", "El Paso", "America/Denver"),
+];
+
+/// Get the timezone a temple operates in, based on where it is located.
+pub fn temple_timezone(country_name: &str, region_name: &str, city: &str) -> &'static Tz {
+    let country = normalize(country_name);
+    let state_region = normalize(region_name);
+    let city = normalize(city);
+
+    let from_city = CITY_TIMEZONES.iter().find(|(c, r, ci, _)| {
+        normalize(c) == country
+            && (r.is_empty() || normalize(r) == state_region)
+            && normalize(ci) == city
+    });
+    let from_region = REGION_TIMEZONES
+        .iter()
+        .find(|(c, r, _)| normalize(c) == country && normalize(r) == state_region);
+    let from_country = COUNTRY_TIMEZONES
+        .iter()
+        .find(|(c, _)| normalize(c) == country);
+
+    let name = match (from_city, from_region, from_country) {
+        (Some((_, _, _, tz)), _, _) | (None, Some((_, _, tz)), _) => *tz,
+        (None, None, Some((c, tz))) => {
+            if REGION_TIMEZONES.iter().any(|(rc, _, _)| rc == c) {
+                warn_once(format!(
+                    "Warning: Unknown region {} in {}, so assuming {}. Times may be off for temples there.",
+                    region_name, c, tz
+                ));
+            }
+            *tz
+        }
+        (None, None, None) => {
+            warn_once(format!(
+                "Warning: Unknown country {}, so assuming {}. Times may be off for temples there.",
+                country_name, DEFAULT_TIMEZONE
+            ));
+            DEFAULT_TIMEZONE
+        }
+    };
+
+    timezones::get_by_name(name).unwrap_or(timezones::db::UTC)
+}
+
+/// Print a warning to stderr, but only the first time, since timezones are looked up over and over.
+fn warn_once(warning: String) {
+    static WARNED: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
+    if WARNED.lock().unwrap().insert(warning.clone()) {
+        eprintln!("{}", warning);
+    }
+}
+
+/// Timezone of the computer running this tool, falling back to UTC if it can't be determined.
+pub fn local_timezone() -> &'static Tz {
+    time_tz::system::get_timezone().unwrap_or(timezones::db::UTC)
+}
+
+/// Current time in the given timezone.
+pub fn now_in(timezone: &'static Tz) -> OffsetDateTime {
+    OffsetDateTime::now_utc().to_timezone(timezone)
+}
+
+/// Lowercase a location name and strip the accents that show up in Spanish and Portuguese place names
+/// so lookups aren't thrown off by how the temple list happens to spell them.
+fn normalize(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            '’' => '\'',
+            other => other,
+        })
+        .collect()
+}
+
+/// Which timezone times should be displayed in.
+#[derive(Debug, Clone, Copy)]
+pub enum DisplayTimezone {
+    /// The timezone of the temple the session or appointment is at.
+    Temple,
+
+    /// The timezone of the computer running this tool.
+    Local,
+
+    /// Any IANA timezone, like America/Denver.
+    Named(&'static Tz),
+}
+
+impl DisplayTimezone {
+    /// Pick the timezone to display in, given the timezone of the temple being displayed.
+    pub fn resolve(&self, temple_timezone: &'static Tz) -> &'static Tz {
+        match self {
+            DisplayTimezone::Temple => temple_timezone,
+            DisplayTimezone::Local => local_timezone(),
+            DisplayTimezone::Named(tz) => tz,
+        }
+    }
+}
+
+impl FromStr for DisplayTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "temple" => Ok(DisplayTimezone::Temple),
+            "local" => Ok(DisplayTimezone::Local),
+            _ => timezones::get_by_name(s)
+                .map(DisplayTimezone::Named)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown timezone {}. Use temple, local or an IANA name like America/Denver",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time_tz::TimeZone;
+
+    fn name(country: &str, state_region: &str, city: &str) -> &'static str {
+        temple_timezone(country, state_region, city).name()
+    }
+
+    #[test]
+    fn timezone_table() {
+        assert_eq!(name("United States", "Hawaii", "Laie"), "Pacific/Honolulu");
+        assert_eq!(name("United States", "Texas", "Houston"), "America/Chicago");
+        assert_eq!(name("United States", "Texas", "El Paso"), "America/Denver");
+        assert_eq!(name("Brazil", "São Paulo", "Campinas"), "America/Sao_Paulo");
+        assert_eq!(
+            name("Brazil", "Rondônia", "Porto Velho"),
+            "America/Porto_Velho"
+        );
+        assert_eq!(name("Brazil", "Paraná", "Curitiba"), "America/Sao_Paulo");
+        assert_eq!(
+            name("Mexico", "Nuevo León", "Monterrey"),
+            "America/Mexico_City"
+        );
+        assert_eq!(name("Canada", "Québec", "Montréal"), "America/Toronto");
+        assert_eq!(name("Japan", "", "Tokyo"), "Asia/Tokyo");
+    }
+
+    #[test]
+    fn unknown_places_fall_back() {
+        assert_eq!(name("United States", "Atlantis", ""), "America/Denver");
+        assert_eq!(name("Nowhere", "", ""), "Etc/UTC");
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    data::{AppointmentJSON, ZonedDateTime},
+    locale::Lang,
+    timezone::DisplayTimezone,
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+use term_table::{row::Row, table_cell::TableCell};
+use time::{macros::format_description, serde::rfc3339, Date, OffsetDateTime};
+use time_tz::TimeZone;
+
+/// One row per appointment, for loading into spreadsheets and other tools.
+#[derive(Serialize)]
+struct AppointmentRow {
+    date: Date,
+    time: String,
+    timezone: &'static str,
+    ordinance: String,
+    temple: String,
+    temple_id: Option<u32>,
+    status: String,
+    confirmation_number: Option<String>,
+    guests: u32,
+    proxy_names: String,
+    language: Option<String>,
+}
+
+/// An appointment for scripts, with its start time including the offset.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppointmentExport<'a> {
+    #[serde(with = "rfc3339")]
+    start: OffsetDateTime,
+    timezone: &'static str,
+    ordinance: String,
+    temple: String,
+    temple_id: Option<u32>,
+    status: String,
+    confirmation_number: Option<&'a str>,
+    guests: u32,
+    proxy_names: &'a [String],
+    language: Option<&'a str>,
+}
+
+/// When the appointment is, in the timezone it should be shown in.
+fn start(appointment: &AppointmentJSON, timezone: DisplayTimezone) -> ZonedDateTime {
+    let time = appointment.date_time();
+    time.to_timezone(timezone.resolve(time.timezone()))
+}
+
+pub fn write_list(
+    appointments: &[AppointmentJSON],
+    timezone: DisplayTimezone,
+    lang: Lang,
+    output: &mut dyn Write,
+) -> Result<()> {
+    for appointment in appointments {
+        writeln!(output, "{}", appointment.display_in(timezone, lang))?;
+    }
+    Ok(())
+}
+
+pub fn write_table(
+    appointments: &[AppointmentJSON],
+    timezone: DisplayTimezone,
+    lang: Lang,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let labels = lang.labels();
+    let mut table = term_table::Table::new();
+    table.max_column_width = 80;
+    table.style = term_table::TableStyle::extended();
+
+    table.add_row(Row::new([
+        TableCell::new(labels.date),
+        TableCell::new(labels.time),
+        TableCell::new(labels.ordinance),
+        TableCell::new(labels.temple),
+        TableCell::new(labels.status),
+        TableCell::new(labels.confirmation),
+        TableCell::new(labels.guests),
+    ]));
+
+    for appointment in appointments {
+        let time = start(appointment, timezone);
+        table.add_row(Row::new([
+            TableCell::new(lang.date(time.date())),
+            TableCell::new(format!(
+                "{} {}",
+                lang.time(time.local().time()),
+                time.abbreviation()
+            )),
+            TableCell::new(appointment.ordinance_name(lang)),
+            TableCell::new(appointment.temple_display_name()),
+            TableCell::new(lang.appointment_status(appointment.appointment_status)),
+            TableCell::new(appointment.confirmation_number.as_deref().unwrap_or("")),
+            TableCell::new(appointment.guest_count()),
+        ]));
+    }
+
+    table.add_row(Row::new([TableCell::new(
+        lang.total_count(appointments.len()),
+    )]));
+
+    writeln!(output, "{}", table.render())?;
+    Ok(())
+}
+
+pub fn write_json(
+    appointments: &[AppointmentJSON],
+    timezone: DisplayTimezone,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let export: Vec<AppointmentExport> = appointments
+        .iter()
+        .map(|appointment| {
+            let time = start(appointment, timezone);
+            AppointmentExport {
+                start: time.local(),
+                timezone: time.timezone().name(),
+                ordinance: appointment.ordinance_name(Lang::En),
+                temple: appointment.temple_display_name(),
+                temple_id: appointment.temple_org_id,
+                status: appointment.appointment_status.to_string(),
+                confirmation_number: appointment.confirmation_number.as_deref(),
+                guests: appointment.guest_count(),
+                proxy_names: &appointment.proxy_names,
+                language: appointment.language.as_deref(),
+            }
+        })
+        .collect();
+
+    serde_json::to_writer_pretty(&mut *output, &export)?;
+    writeln!(output)?;
+    Ok(())
+}
+
+pub fn write_csv(
+    appointments: &[AppointmentJSON],
+    timezone: DisplayTimezone,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(output);
+    let time_format = format_description!("[hour]:[minute]");
+
+    for appointment in appointments {
+        let time = start(appointment, timezone);
+        writer.serialize(AppointmentRow {
+            date: time.date(),
+            time: time.local().format(&time_format)?,
+            timezone: time.timezone().name(),
+            ordinance: appointment.ordinance_name(Lang::En),
+            temple: appointment.temple_display_name(),
+            temple_id: appointment.temple_org_id,
+            status: appointment.appointment_status.to_string(),
+            confirmation_number: appointment.confirmation_number.clone(),
+            guests: appointment.guest_count(),
+            proxy_names: appointment.proxy_names.join("; "),
+            language: appointment.language.clone(),
+        })?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use clap::ValueEnum;
+
+use crate::locale::Lang;
+
+use super::{
+    grid::{CellState, ScheduleGrid},
+    OutputOptions,
+};
+
+/// Colors used to shade open slots, from few seats to many, along with the colors for the other states.
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum Palette {
+    /// Greens for open slots and red for full ones
+    #[default]
+    Classic,
+
+    /// Yellow to purple, readable with any kind of color blindness
+    Viridis,
+
+    /// Yellow to blue, designed for red-green color blindness
+    Cividis,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// How slots are colored, based on the palette and thresholds chosen and the most open seats in the
+/// schedule.
+#[derive(Debug, Clone)]
+pub struct ColorScale {
+    palette: Palette,
+
+    /// Boundaries between color steps. Without any, open slots get a continuous gradient.
+    thresholds: Vec<u32>,
+    max_seats: u32,
+    group_size: Option<u32>,
+    lang: Lang,
+}
+
+pub struct LegendEntry {
+    pub label: String,
+    pub fill: Rgb,
+
+    /// The kind of slot this entry stands for. Open entries carry the fewest seats they cover.
+    pub state: CellState,
+}
+
+impl Rgb {
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        (self.0 as u32) << 16 | (self.1 as u32) << 8 | self.2 as u32
+    }
+
+    /// Black or white, whichever is easier to read on top of this color.
+    pub fn text_color(&self) -> Rgb {
+        let luminance = 0.299 * self.0 as f32 + 0.587 * self.1 as f32 + 0.114 * self.2 as f32;
+        if luminance > 150.0 {
+            Rgb(0x21, 0x25, 0x29)
+        } else {
+            Rgb(0xff, 0xff, 0xff)
+        }
+    }
+}
+
+impl Palette {
+    /// Stops of the gradient for open slots, from few seats to many.
+    fn stops(&self) -> &'static [Rgb] {
+        match self {
+            Palette::Classic => &[
+                Rgb(0xd1, 0xe7, 0xdd),
+                Rgb(0x19, 0x87, 0x54),
+                Rgb(0x0f, 0x51, 0x32),
+            ],
+            Palette::Viridis => &[
+                Rgb(0xfd, 0xe7, 0x25),
+                Rgb(0x5e, 0xc9, 0x62),
+                Rgb(0x21, 0x91, 0x8c),
+                Rgb(0x3b, 0x52, 0x8b),
+                Rgb(0x44, 0x01, 0x54),
+            ],
+            Palette::Cividis => &[
+                Rgb(0xfe, 0xe8, 0x38),
+                Rgb(0xc3, 0xb3, 0x69),
+                Rgb(0x8d, 0x88, 0x78),
+                Rgb(0x57, 0x5c, 0x6d),
+                Rgb(0x00, 0x20, 0x4d),
+            ],
+        }
+    }
+
+    fn full(&self) -> Rgb {
+        match self {
+            Palette::Classic => Rgb(0xdc, 0x35, 0x45),
+            // Vermillion stays distinct from both ends of these gradients for every kind of color blindness.
+            Palette::Viridis | Palette::Cividis => Rgb(0xd5, 0x5e, 0x00),
+        }
+    }
+}
+
+impl ColorScale {
+    pub fn new(options: &OutputOptions, grid: &ScheduleGrid) -> Self {
+        let mut thresholds = options.thresholds.clone();
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        thresholds.retain(|t| *t > 1);
+
+        Self {
+            palette: options.palette,
+            thresholds,
+            max_seats: grid.max_open_seats(),
+            group_size: options.group_size,
+            lang: grid.lang,
+        }
+    }
+
+    pub fn fill(&self, state: CellState) -> Rgb {
+        match state {
+            CellState::Open(seats) => self.open(seats),
+            CellState::Full => self.palette.full(),
+            CellState::Closed => Rgb(0x21, 0x25, 0x29),
+            CellState::None => Rgb(0x6c, 0x75, 0x7d),
+        }
+    }
+
+    /// Color for a slot with this many open seats. With thresholds each step gets an evenly spaced
+    /// color from the palette, otherwise the color is scaled between 1 seat and the most in the schedule.
+    pub fn open(&self, seats: u32) -> Rgb {
+        let position = if self.thresholds.is_empty() {
+            if self.max_seats > 1 {
+                (seats.saturating_sub(1) as f32 / (self.max_seats - 1) as f32).min(1.0)
+            } else {
+                1.0
+            }
+        } else {
+            let step = self.thresholds.iter().filter(|t| seats >= **t).count();
+            step as f32 / self.thresholds.len() as f32
+        };
+
+        interpolate(self.palette.stops(), position)
+    }
+
+    /// Slots worth pointing out because one of their sessions has room for the whole group.
+    pub fn group_size(&self) -> Option<u32> {
+        self.group_size
+    }
+
+    pub fn legend(&self) -> Vec<LegendEntry> {
+        let mut entries = vec![];
+        if self.thresholds.is_empty() {
+            let mut samples = vec![1, self.max_seats.div_ceil(2), self.max_seats];
+            samples.dedup();
+            // Nothing is open when every slot is full, so there's no gradient to show.
+            samples.retain(|seats| (1..=self.max_seats).contains(seats));
+            for seats in samples {
+                entries.push(LegendEntry {
+                    label: self.lang.open_range(seats, Some(seats)),
+                    fill: self.open(seats),
+                    state: CellState::Open(seats),
+                });
+            }
+        } else {
+            let mut lower = 1;
+            for &threshold in &self.thresholds {
+                entries.push(LegendEntry {
+                    label: self.lang.open_range(lower, Some(threshold - 1)),
+                    fill: self.open(lower),
+                    state: CellState::Open(lower),
+                });
+                lower = threshold;
+            }
+            entries.push(LegendEntry {
+                label: self.lang.open_range(lower, None),
+                fill: self.open(lower),
+                state: CellState::Open(lower),
+            });
+        }
+
+        let labels = self.lang.labels();
+        for (label, state) in [
+            (labels.full, CellState::Full),
+            (labels.closed, CellState::Closed),
+            (labels.no_session, CellState::None),
+        ] {
+            entries.push(LegendEntry {
+                label: label.to_string(),
+                fill: self.fill(state),
+                state,
+            });
+        }
+
+        entries
+    }
+}
+
+/// Color at `position` (0 to 1) along evenly spaced gradient stops.
+fn interpolate(stops: &[Rgb], position: f32) -> Rgb {
+    let scaled = position.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = (scaled.floor() as usize).min(stops.len() - 2);
+    let t = scaled - index as f32;
+    let (a, b) = (stops[index], stops[index + 1]);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+    Rgb(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scale(max_seats: u32) -> ColorScale {
+        ColorScale {
+            palette: Palette::Classic,
+            thresholds: vec![],
+            max_seats,
+            group_size: None,
+            lang: Lang::En,
+        }
+    }
+
+    fn states(scale: &ColorScale) -> Vec<CellState> {
+        scale.legend().iter().map(|entry| entry.state).collect()
+    }
+
+    #[test]
+    fn legend_samples_the_gradient() {
+        assert_eq!(
+            states(&scale(9)),
+            [
+                CellState::Open(1),
+                CellState::Open(5),
+                CellState::Open(9),
+                CellState::Full,
+                CellState::Closed,
+                CellState::None,
+            ]
+        );
+    }
+
+    #[test]
+    fn no_gradient_without_open_seats() {
+        assert_eq!(
+            states(&scale(0)),
+            [CellState::Full, CellState::Closed, CellState::None]
+        );
+        assert_eq!(
+            states(&scale(1)),
+            [
+                CellState::Open(1),
+                CellState::Full,
+                CellState::Closed,
+                CellState::None,
+            ]
+        );
+    }
+}
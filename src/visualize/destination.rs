@@ -0,0 +1,108 @@
+use anyhow::{bail, Context, Result};
+use std::{
+    fs::File,
+    io::{BufWriter, IsTerminal, Write},
+    path::PathBuf,
+};
+
+/// Where output is written: a file, or stdout if the path given is `-`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    Stdout,
+    File(PathBuf),
+}
+
+impl Destination {
+    /// Pick the destination for a path from the command line. Paths without an extension get the one
+    /// for the format. Without a path, output goes to `default_name` with that extension, or to stdout
+    /// if `default_name` is None.
+    pub fn new(path: Option<&str>, default_name: Option<&str>, extension: &str) -> Self {
+        match path.or(default_name) {
+            Some("-") | None => Destination::Stdout,
+            Some(path) => {
+                let mut path = PathBuf::from(path);
+                if path.extension().is_none() {
+                    path.set_extension(extension);
+                }
+                Destination::File(path)
+            }
+        }
+    }
+
+    /// Whether output goes straight to a person's terminal.
+    pub fn is_terminal(&self) -> bool {
+        *self == Destination::Stdout && std::io::stdout().is_terminal()
+    }
+
+    /// Fail early if writing here would replace a file and `force` isn't set, so we don't fetch a
+    /// schedule only to throw it away.
+    pub fn check(&self, force: bool) -> Result<()> {
+        match self {
+            Destination::File(path) if !force && path.exists() => bail!(
+                "{} already exists. Use --force to overwrite it.",
+                path.display()
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn create(&self, force: bool) -> Result<Box<dyn Write>> {
+        self.check(force)?;
+        Ok(match self {
+            Destination::Stdout => Box::new(std::io::stdout().lock()),
+            Destination::File(path) => {
+                Box::new(BufWriter::new(File::create(path).with_context(|| {
+                    format!("Unable to create {}", path.display())
+                })?))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_get_the_format_extension() {
+        assert_eq!(
+            Destination::new(Some("-"), Some("provo"), "csv"),
+            Destination::Stdout
+        );
+        assert_eq!(Destination::new(None, None, "csv"), Destination::Stdout);
+        assert_eq!(
+            Destination::new(None, Some("provo"), "csv"),
+            Destination::File(PathBuf::from("provo.csv"))
+        );
+        assert_eq!(
+            Destination::new(Some("provo.txt"), None, "csv"),
+            Destination::File(PathBuf::from("provo.txt"))
+        );
+    }
+
+    #[test]
+    fn refuses_to_overwrite_without_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedule");
+        let destination = Destination::new(path.to_str(), None, "csv");
+        destination
+            .create(false)
+            .unwrap()
+            .write_all(b"first")
+            .unwrap();
+
+        let error = destination.create(false).err().unwrap();
+        assert!(error.to_string().contains("--force"));
+        assert_eq!(std::fs::read(path.with_extension("csv")).unwrap(), b"first");
+
+        destination
+            .create(true)
+            .unwrap()
+            .write_all(b"second")
+            .unwrap();
+        assert_eq!(
+            std::fs::read(path.with_extension("csv")).unwrap(),
+            b"second"
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{
+    data::Availability,
+    locale::{Labels, Lang},
+    snapshot::{ChangeKind, ScheduleDiff, SessionChange},
+    timezone::DisplayTimezone,
+};
+use anyhow::Result;
+use minijinja::Environment;
+use serde::Serialize;
+use std::io::Write;
+use term_table::{row::Row, table_cell::TableCell};
+use time_tz::{Offset, OffsetDateTimeExt, TimeZone, Tz};
+use xlsxwriter::{format::FormatColor, Format, Workbook};
+
+use super::excel::write_workbook;
+
+const TEMPLATE: &str = include_str!("templates/diff.html");
+
+/// Fills for the change column, matching the classic palette.
+const ADDED: u32 = 0xd1e7dd;
+const FULL: u32 = 0xdc3545;
+
+/// Everything the diff template uses.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiffReport<'a> {
+    temple: &'a str,
+    lang: &'static str,
+    text: &'static Labels,
+    compared: String,
+    summary: String,
+    rows: Vec<ChangeRow>,
+}
+
+/// A changed session as shown in every format.
+#[derive(Serialize)]
+struct ChangeRow {
+    date: String,
+    time: String,
+
+    /// One of added, removed, full, closed or seats, for styling.
+    kind: &'static str,
+
+    change: &'static str,
+    before: String,
+    after: String,
+    difference: Option<i64>,
+
+    #[serde(skip)]
+    before_seats: Option<u32>,
+    #[serde(skip)]
+    after_seats: Option<u32>,
+    #[serde(skip)]
+    change_kind: ChangeKind,
+}
+
+/// Lays out the diff once for all the formats.
+struct Report<'a> {
+    diff: &'a ScheduleDiff,
+    compared: String,
+    summary: String,
+    rows: Vec<ChangeRow>,
+}
+
+impl<'a> Report<'a> {
+    fn new(diff: &'a ScheduleDiff, timezone: DisplayTimezone, lang: Lang) -> Self {
+        let timezone = timezone.resolve(diff.temple.timezone());
+        let old = diff.old_saved_at.to_timezone(timezone);
+        let new = diff.new_saved_at.to_timezone(timezone);
+
+        Self {
+            diff,
+            compared: lang.compared(old, new, timezone.get_offset_utc(&new).name()),
+            summary: lang.diff_totals(
+                diff.seats_taken(),
+                diff.count(ChangeKind::BecameFull),
+                diff.count(ChangeKind::Added),
+                diff.count(ChangeKind::Removed),
+                diff.days_compared,
+            ),
+            rows: diff
+                .changes
+                .iter()
+                .map(|change| change_row(change, timezone, lang))
+                .collect(),
+        }
+    }
+}
+
+fn change_row(change: &SessionChange, timezone: &'static Tz, lang: Lang) -> ChangeRow {
+    let local = change.time.to_timezone(timezone).local();
+    let kind = change.kind();
+    let seats = |availability: Option<Availability>| match availability {
+        Some(Availability::Open(remaining)) => Some(remaining),
+        _ => None,
+    };
+
+    ChangeRow {
+        date: lang.date(local.date()),
+        time: lang.time(local.time()),
+        kind: match kind {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::BecameFull => "full",
+            ChangeKind::Closed => "closed",
+            ChangeKind::Seats => "seats",
+        },
+        change: lang.change_kind(kind),
+        before: availability_text(change.old, lang),
+        after: availability_text(change.new, lang),
+        difference: change.seat_change(),
+        before_seats: seats(change.old),
+        after_seats: seats(change.new),
+        change_kind: kind,
+    }
+}
+
+/// Open seats, Full or Closed, or a dash if the session isn't in that snapshot.
+fn availability_text(availability: Option<Availability>, lang: Lang) -> String {
+    match availability {
+        Some(Availability::Open(remaining)) => remaining.to_string(),
+        Some(Availability::Full) => lang.labels().full.to_string(),
+        Some(Availability::Closed) => lang.labels().closed.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn signed(difference: Option<i64>) -> String {
+    match difference {
+        Some(difference) if difference > 0 => format!("+{}", difference),
+        Some(difference) => difference.to_string(),
+        None => String::new(),
+    }
+}
+
+pub fn write_terminal(
+    diff: &ScheduleDiff,
+    timezone: DisplayTimezone,
+    lang: Lang,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let report = Report::new(diff, timezone, lang);
+    let labels = lang.labels();
+
+    writeln!(output, "{}", report.diff.temple.name)?;
+    writeln!(output, "{}", labels.session_changes)?;
+    writeln!(output, "{}", report.compared)?;
+    writeln!(output, "{}", report.summary)?;
+    writeln!(output)?;
+
+    if report.rows.is_empty() {
+        writeln!(output, "{}", labels.no_changes)?;
+        return Ok(());
+    }
+
+    let mut table = term_table::Table::new();
+    table.style = term_table::TableStyle::thin();
+    table.add_row(Row::new([
+        TableCell::new(labels.date),
+        TableCell::new(labels.time),
+        TableCell::new(labels.change),
+        TableCell::new(labels.before),
+        TableCell::new(labels.after),
+        TableCell::new(labels.difference),
+    ]));
+    for row in &report.rows {
+        table.add_row(Row::new([
+            TableCell::new(&row.date),
+            TableCell::new(&row.time),
+            TableCell::new(row.change),
+            TableCell::new(&row.before),
+            TableCell::new(&row.after),
+            TableCell::new(signed(row.difference)),
+        ]));
+    }
+    write!(output, "{}", table.render())?;
+    writeln!(output)?;
+
+    Ok(())
+}
+
+pub fn write_html(
+    diff: &ScheduleDiff,
+    timezone: DisplayTimezone,
+    lang: Lang,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let report = Report::new(diff, timezone, lang);
+
+    let mut env = Environment::new();
+    env.add_template("diff.html", TEMPLATE)?;
+    let html = env.get_template("diff.html")?.render(DiffReport {
+        temple: &diff.temple.name,
+        lang: lang.code(),
+        text: lang.labels(),
+        compared: report.compared,
+        summary: report.summary,
+        rows: report.rows,
+    })?;
+
+    writeln!(output, "{}", html)?;
+
+    Ok(())
+}
+
+pub fn write_excel(
+    diff: &ScheduleDiff,
+    timezone: DisplayTimezone,
+    lang: Lang,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let report = Report::new(diff, timezone, lang);
+    write_workbook(output, |workbook| write_changes(workbook, &report, lang))
+}
+
+fn write_changes(workbook: &Workbook, report: &Report, lang: Lang) -> Result<()> {
+    let diff = report.diff;
+    let labels = lang.labels();
+
+    let mut sheet = workbook.add_worksheet(Some(labels.session_changes))?;
+
+    let mut bold = Format::new();
+    bold.set_bold();
+    sheet.write_string(0, 0, &diff.temple.name, Some(&bold))?;
+    sheet.write_string(1, 0, &report.compared, None)?;
+    sheet.write_string(2, 0, &report.summary, None)?;
+
+    let headers = [
+        labels.date,
+        labels.time,
+        labels.change,
+        labels.before,
+        labels.after,
+        labels.difference,
+    ];
+    for (col, header) in (0..).zip(headers) {
+        sheet.write_string(4, col, header, Some(&bold))?;
+    }
+
+    let mut added = Format::new();
+    added.set_bg_color(FormatColor::Custom(ADDED));
+    let mut full = Format::new();
+    full.set_bg_color(FormatColor::Custom(FULL))
+        .set_font_color(FormatColor::White);
+
+    let mut row = 4;
+    for change in &report.rows {
+        row += 1;
+        let format = match change.change_kind {
+            ChangeKind::Added => Some(&added),
+            ChangeKind::BecameFull => Some(&full),
+            _ => None,
+        };
+
+        sheet.write_string(row, 0, &change.date, None)?;
+        sheet.write_string(row, 1, &change.time, None)?;
+        sheet.write_string(row, 2, change.change, format)?;
+        for (col, seats, text) in [
+            (3, change.before_seats, &change.before),
+            (4, change.after_seats, &change.after),
+        ] {
+            match seats {
+                Some(seats) => sheet.write_number(row, col, seats.into(), None)?,
+                None => sheet.write_string(row, col, text, None)?,
+            }
+        }
+        if let Some(difference) = change.difference {
+            sheet.write_number(row, 5, difference as f64, None)?;
+        }
+    }
+
+    if report.rows.is_empty() {
+        sheet.write_string(5, 0, labels.no_changes, None)?;
+    } else {
+        sheet.autofilter(4, 0, row, headers.len() as u16 - 1)?;
+    }
+    sheet.freeze_panes(5, 0);
+    sheet.set_column(0, 1, 14.0, None)?;
+    sheet.set_column(2, 2, 20.0, None)?;
+    sheet.set_column(3, 5, 12.0, None)?;
+
+    Ok(())
+}
@@ -1,119 +1,564 @@
-use crate::{data::Day, Temple};
-use anyhow::Result;
-use std::collections::HashMap;
-use time::macros::format_description;
-use xlsxwriter::{format::FormatAlignment, format::FormatColor, Format, Workbook};
+use crate::{data::Availability, locale::Lang, Temple};
+use anyhow::{Context, Result};
+use std::{collections::BTreeMap, io::Write};
+use time::{Date, Time, UtcOffset, Weekday};
+use xlsxwriter::{
+    chart::{Chart, ChartFill, ChartType},
+    format::FormatAlignment,
+    format::FormatBorder,
+    format::FormatColor,
+    worksheet::DateTime,
+    Format, Workbook, Worksheet,
+};
 
-use super::OutputWriter;
+use super::{
+    color::ColorScale,
+    grid::{CalendarMonth, Cell, CellState, ScheduleGrid, Week},
+    Layout, OutputOptions, OutputWriter,
+};
 
+/// Writes a workbook with a summary sheet and charts, a sheet per week laid out like the other outputs
+/// (or per month in the calendar layout), and a sheet with every session as a row for filtering.
 pub struct ExcelWriter;
 
+/// How many of the slots with the most open seats to list on the summary sheet.
+const BEST_TIMES: usize = 5;
+
 impl OutputWriter for ExcelWriter {
-    fn write_output(schedules: &[Day], temple: &Temple, filename: &str) -> Result<()> {
-        const START_HOUR: u8 = 5;
-        const END_HOUR: u8 = 20;
+    fn extension(&self) -> &'static str {
+        "xlsx"
+    }
 
-        let workbook = Workbook::new(&format!("{filename}.xlsx"))?;
-        let mut red_format = Format::new();
-        red_format
-            .set_bg_color(FormatColor::Red)
-            .set_align(FormatAlignment::Center);
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        options: &OutputOptions,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        write_workbook(output, |workbook| {
+            let colors = ColorScale::new(options, grid);
+            let labels = grid.lang.labels();
 
-        let mut green_format = Format::new();
-        green_format
-            .set_bg_color(FormatColor::Green)
-            .set_align(FormatAlignment::Center);
+            let mut summary = workbook.add_worksheet(Some(labels.summary))?;
+            write_summary(workbook, &mut summary, grid, temple, &colors)?;
 
-        let mut sheet = workbook.add_worksheet(None)?;
+            match options.layout {
+                Layout::Grid => {
+                    for week in grid.weeks() {
+                        let name = grid.lang.week_of(week.start);
+                        let mut sheet = workbook.add_worksheet(Some(&name))?;
+                        write_week(&mut sheet, grid, temple, &week, &colors)?;
+                    }
+                }
+                Layout::Calendar => {
+                    for month in grid.months() {
+                        let name = grid.lang.month_year(month.start);
+                        let mut sheet = workbook.add_worksheet(Some(&name))?;
+                        write_month(&mut sheet, grid, temple, &month, &colors)?;
+                    }
+                }
+            }
 
-        sheet.merge_range(
-            0,
-            0,
-            0,
-            (schedules.len() + 1) as u16,
-            temple.name.as_str(),
-            Some(Format::new().set_bold()),
-        )?;
-        sheet.merge_range(
-            1,
-            0,
+            let mut sessions = workbook.add_worksheet(Some(labels.sessions))?;
+            write_sessions(&mut sessions, grid)?;
+
+            Ok(())
+        })
+    }
+}
+
+/// xlsxwriter can only save to a path, so build the workbook in a temporary file and copy it to the
+/// output once it's closed.
+pub(super) fn write_workbook(
+    output: &mut dyn Write,
+    build: impl FnOnce(&Workbook) -> Result<()>,
+) -> Result<()> {
+    let file = tempfile::Builder::new().suffix(".xlsx").tempfile()?;
+    let path = file
+        .path()
+        .to_str()
+        .context("Temporary path isn't valid UTF-8")?;
+
+    let workbook = Workbook::new(path)?;
+    build(&workbook)?;
+    workbook.close()?;
+
+    std::io::copy(&mut file.reopen()?, output)?;
+    Ok(())
+}
+
+/// Totals by day and by hour with a chart of each, the best times to go, and the fully booked days.
+fn write_summary(
+    workbook: &Workbook,
+    sheet: &mut Worksheet,
+    grid: &ScheduleGrid,
+    temple: &Temple,
+    colors: &ColorScale,
+) -> Result<()> {
+    let labels = grid.lang.labels();
+    let mut bold = Format::new();
+    bold.set_bold();
+
+    sheet.write_string(0, 0, &temple.name, Some(&bold))?;
+    sheet.write_string(1, 0, labels.available_slots, None)?;
+    sheet.write_string(2, 0, &grid.summary(), None)?;
+    sheet.set_column(0, 0, 14.0, None)?;
+    sheet.set_column(1, 4, 13.0, None)?;
+
+    // Open seats by day
+    let mut row = 4;
+    sheet.write_string(row, 0, labels.open_seats_by_day, Some(&bold))?;
+    row += 1;
+    for (col, header) in (0..).zip([
+        labels.day,
+        labels.open_seats,
+        labels.sessions,
+        labels.open_sessions,
+        labels.fully_booked,
+    ]) {
+        sheet.write_string(row, col, header, Some(&bold))?;
+    }
+    let first_day_row = row + 1;
+    for (column, date) in grid.columns.iter().enumerate() {
+        row += 1;
+        let cells = grid.column(column);
+        let sessions = cells.iter().flat_map(|c| &c.sessions);
+        let open_sessions = sessions
+            .clone()
+            .filter(|s| matches!(s.details.availability(), Availability::Open(_)))
+            .count();
+
+        sheet.write_string(row, 0, &grid.column_label(column), None)?;
+        sheet.write_number(
+            row,
             1,
-            (schedules.len() + 1) as u16,
-            "Available slots for endowment",
-            Some(&Format::new()),
+            cells
+                .iter()
+                .map(|c| c.state.open_seats())
+                .sum::<u32>()
+                .into(),
+            None,
         )?;
+        sheet.write_number(row, 2, sessions.count() as f64, None)?;
+        sheet.write_number(row, 3, open_sessions as f64, None)?;
+        if grid.stats.fully_booked_days.contains(date) {
+            sheet.write_string(row, 4, labels.yes, None)?;
+        }
+    }
+    let by_day = (first_day_row, row);
+
+    // Open seats by the hour sessions start in, across all days
+    let mut hours: BTreeMap<u8, u32> = BTreeMap::new();
+    for session in grid.days.iter().flat_map(|d| &d.sessions) {
+        *hours.entry(session.time.local().hour()).or_default() +=
+            match session.details.availability() {
+                Availability::Open(remaining) => remaining,
+                _ => 0,
+            };
+    }
+
+    row += 2;
+    sheet.write_string(row, 0, labels.open_seats_by_hour, Some(&bold))?;
+    row += 1;
+    sheet.write_string(row, 0, labels.hour, Some(&bold))?;
+    sheet.write_string(row, 1, labels.open_seats, Some(&bold))?;
+    let first_hour_row = row + 1;
+    for (hour, seats) in &hours {
+        row += 1;
+        sheet.write_string(row, 0, &grid.lang.time(Time::from_hms(*hour, 0, 0)?), None)?;
+        sheet.write_number(row, 1, (*seats).into(), None)?;
+    }
+    let by_hour = (first_hour_row, row);
+
+    // Best times
+    let mut slots: Vec<(u32, Time)> = grid
+        .open_seats_by_row()
+        .into_iter()
+        .zip(grid.rows.iter().copied())
+        .filter(|(seats, _)| *seats > 0)
+        .collect();
+    slots.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
 
-        let mut row = 3;
-        for hour in START_HOUR..END_HOUR {
-            for minutes in [0, 30] {
-                if hour <= 12 {
-                    sheet.write_string(row, 0, &format!("{}:{:02} AM", hour, minutes), None)?;
-                } else {
-                    sheet.write_string(
-                        row,
-                        0,
-                        &format!("{}:{:02} PM", hour - 12, minutes),
-                        None,
-                    )?;
+    row += 2;
+    sheet.write_string(row, 0, labels.best_times, Some(&bold))?;
+    row += 1;
+    sheet.write_string(row, 0, labels.time, Some(&bold))?;
+    sheet.write_string(row, 1, labels.open_seats, Some(&bold))?;
+    for (seats, slot) in slots.into_iter().take(BEST_TIMES) {
+        row += 1;
+        sheet.write_string(row, 0, &grid.lang.time(slot), None)?;
+        sheet.write_number(row, 1, seats.into(), None)?;
+    }
+
+    if !grid.columns.is_empty() {
+        let chart = seats_chart(workbook, grid, labels.open_seats_by_day, by_day, colors)?;
+        sheet.insert_chart(4, 6, &chart)?;
+    }
+    if !hours.is_empty() {
+        let chart = seats_chart(workbook, grid, labels.open_seats_by_hour, by_hour, colors)?;
+        // Next to its table, but below the first chart, which is about 15 rows tall.
+        sheet.insert_chart((by_hour.0 - 2).max(20), 6, &chart)?;
+    }
+
+    Ok(())
+}
+
+/// Column chart of the labels and open seats in the first two columns of the summary sheet, between
+/// the given rows.
+fn seats_chart<'a>(
+    workbook: &'a Workbook,
+    grid: &ScheduleGrid,
+    title: &str,
+    (first_row, last_row): (u32, u32),
+    colors: &ColorScale,
+) -> Result<Chart<'a>> {
+    let mut chart = workbook.add_chart(ChartType::Column);
+    chart.add_title(title)?;
+
+    let labels = grid.lang.labels();
+    let mut series = chart.add_series(None, None)?;
+    series.set_categories(labels.summary, first_row, 0, last_row, 0)?;
+    series.set_values(labels.summary, first_row, 1, last_row, 1)?;
+    series.set_name(labels.open_seats)?;
+    series.set_fill(&ChartFill {
+        color: FormatColor::Custom(colors.open(u32::MAX).as_u32()),
+        ..ChartFill::new()
+    });
+
+    Ok(chart)
+}
+
+/// The days of one week laid out like the other outputs, with a row per time slot.
+fn write_week(
+    sheet: &mut Worksheet,
+    grid: &ScheduleGrid,
+    temple: &Temple,
+    week: &Week,
+    colors: &ColorScale,
+) -> Result<()> {
+    let labels = grid.lang.labels();
+    let num_columns = week.columns.len() as u16;
+
+    sheet.merge_range(
+        0,
+        0,
+        0,
+        num_columns + 1,
+        temple.name.as_str(),
+        Some(Format::new().set_bold()),
+    )?;
+    sheet.merge_range(
+        1,
+        0,
+        1,
+        num_columns + 1,
+        labels.available_slots,
+        Some(&Format::new()),
+    )?;
+
+    for row in 0..grid.rows.len() {
+        sheet.write_string(3 + row as u32, 0, &grid.row_label(row), None)?;
+    }
+
+    for (col, &column) in (1..).zip(&week.columns) {
+        sheet.write_string(2, col, &grid.column_label(column), None)?;
+
+        for ((row, cell), slot) in (3..).zip(grid.column(column)).zip(&grid.rows) {
+            let format = cell_format(colors, cell);
+            match cell.state {
+                CellState::Open(remaining) => {
+                    sheet.write_number(row, col, remaining.into(), Some(&format))?
                 }
+                CellState::Full => sheet.write_number(row, col, 0.0, Some(&format))?,
+                CellState::Closed => sheet.write_string(row, col, labels.closed, Some(&format))?,
+                CellState::None => sheet.write_blank(row, col, Some(&format))?,
+            }
 
-                row += 1;
+            let comment = slot_comment(cell, *slot, grid.lang);
+            if !comment.is_empty() {
+                sheet.write_comment(row, col, &comment)?;
             }
         }
+    }
+    sheet.freeze_panes(3, 1);
 
-        let date_format = format_description!("[weekday repr:short] [month repr:short] [day]");
-        let mut col = 1;
-        for day in schedules {
-            let mut row = 2;
+    write_legend(sheet, grid, 3 + grid.rows.len() as u32 + 1, colors)
+}
 
-            sheet.write_string(row, col, &day.date.format(&date_format).unwrap(), None)?;
+/// A month laid out like a wall calendar. Each week is a row of day numbers followed by a row for each
+/// open session, so a day lists its sessions down its column.
+fn write_month(
+    sheet: &mut Worksheet,
+    grid: &ScheduleGrid,
+    temple: &Temple,
+    month: &CalendarMonth,
+    colors: &ColorScale,
+) -> Result<()> {
+    let labels = grid.lang.labels();
+    sheet.merge_range(
+        0,
+        0,
+        0,
+        6,
+        temple.name.as_str(),
+        Some(Format::new().set_bold()),
+    )?;
+    sheet.merge_range(
+        1,
+        0,
+        1,
+        6,
+        &format!(
+            "{} - {}",
+            labels.available_slots,
+            grid.lang.month_year(month.start)
+        ),
+        Some(&Format::new()),
+    )?;
 
-            row += 1;
+    let mut bold = Format::new();
+    bold.set_bold().set_align(FormatAlignment::Center);
+    for weekday in 0..7 {
+        let name = grid.lang.weekday_short(Weekday::Sunday.nth_next(weekday));
+        sheet.write_string(2, weekday.into(), name, Some(&bold))?;
+    }
+    sheet.set_column(0, 6, 16.0, None)?;
 
-            let hour_counts: HashMap<(u8, u8), u32> = day
-                .sessions
-                .session_list
-                .iter()
-                .map(|s| {
-                    (
-                        (s.time.hour(), s.time.minute()),
-                        if s.details.remaining_online_seats_available < 0 {
-                            0
-                        } else {
-                            s.details.remaining_online_seats_available
-                        } as u32,
-                    )
-                })
-                .collect();
-            for hour in START_HOUR..END_HOUR {
-                for minutes in [0, 30] {
-                    match hour_counts.get(&(hour, minutes)) {
-                        Some(&remaining) => {
-                            let format = if remaining > 0 {
-                                &green_format
-                            } else {
-                                &red_format
-                            };
-                            sheet.write_number(row, col, remaining.into(), Some(format))?;
-                        }
-                        None => sheet.write_blank(
-                            row,
-                            col,
-                            Some(
-                                Format::new()
-                                    .set_bg_color(FormatColor::Gray)
-                                    .set_align(FormatAlignment::Center),
-                            ),
-                        )?,
-                    }
+    let mut day_format = Format::new();
+    day_format
+        .set_bold()
+        .set_bg_color(FormatColor::Custom(0xf1f3f5))
+        .set_border_top(FormatBorder::Thin);
 
-                    row += 1;
+    let mut row = 3;
+    for week in &month.weeks {
+        let mut height = 1;
+        for (col, date) in (0..).zip(week) {
+            match date {
+                Some(date) => {
+                    sheet.write_number(row, col, date.day().into(), Some(&day_format))?;
+                    if let Some(column) = grid.column_of(*date) {
+                        height = height.max(write_day(sheet, grid, column, row + 1, col, colors)?);
+                    }
                 }
+                None => sheet.write_blank(row, col, Some(&day_format))?,
             }
+        }
+        row += height + 1;
+    }
+    sheet.freeze_panes(3, 0);
+
+    write_legend(sheet, grid, row + 1, colors)
+}
+
+/// The open sessions of a day down a column starting at `row`, or a cell saying the day is full or
+/// closed. Returns how many rows were used.
+fn write_day(
+    sheet: &mut Worksheet,
+    grid: &ScheduleGrid,
+    column: usize,
+    row: u32,
+    col: u16,
+    colors: &ColorScale,
+) -> Result<u32> {
+    let labels = grid.lang.labels();
+    let state = grid.day_state(column);
+    let note = match state {
+        CellState::Full => labels.full,
+        CellState::Closed => labels.closed,
+        CellState::None => return Ok(0),
+        CellState::Open(_) => "",
+    };
+    if !note.is_empty() {
+        let fill = colors.fill(state);
+        let mut format = Format::new();
+        format
+            .set_bg_color(FormatColor::Custom(fill.as_u32()))
+            .set_font_color(FormatColor::Custom(fill.text_color().as_u32()));
+        sheet.write_string(row, col, note, Some(&format))?;
+        return Ok(1);
+    }
+
+    let sessions = grid.open_sessions(column);
+    for (row, session) in (row..).zip(&sessions) {
+        let seats = session.details.remaining_seats();
+        let fill = colors.open(seats);
+        let mut format = Format::new();
+        format
+            .set_bg_color(FormatColor::Custom(fill.as_u32()))
+            .set_font_color(FormatColor::Custom(fill.text_color().as_u32()));
+        if matches!(colors.group_size(), Some(size) if seats >= size) {
+            group_border(&mut format);
+        }
+
+        let mut text = format!("{}  {}", grid.lang.time(session.time.local().time()), seats);
+        if session.details.special_session {
+            text.push('*');
+        }
+        sheet.write_string(row, col, &text, Some(&format))?;
+
+        let summary = session.details.summary(grid.lang);
+        if !summary.is_empty() {
+            sheet.write_comment(row, col, &summary)?;
+        }
+    }
+
+    Ok(sessions.len() as u32)
+}
+
+/// Legend starting at `row`, one swatch per row.
+fn write_legend(
+    sheet: &mut Worksheet,
+    grid: &ScheduleGrid,
+    mut legend_row: u32,
+    colors: &ColorScale,
+) -> Result<()> {
+    let labels = grid.lang.labels();
+    sheet.write_string(legend_row, 0, labels.legend, Some(Format::new().set_bold()))?;
+    for entry in colors.legend() {
+        legend_row += 1;
+        sheet.write_blank(
+            legend_row,
+            0,
+            Some(Format::new().set_bg_color(FormatColor::Custom(entry.fill.as_u32()))),
+        )?;
+        sheet.write_string(legend_row, 1, &entry.label, None)?;
+    }
+    if let Some(size) = colors.group_size() {
+        legend_row += 1;
+        sheet.write_blank(legend_row, 0, Some(group_border(&mut Format::new())))?;
+        sheet.write_string(legend_row, 1, &grid.lang.room_for(size), None)?;
+    }
 
-            col += 1;
+    Ok(())
+}
+
+/// Every session as a row, with a filter on each column.
+fn write_sessions(sheet: &mut Worksheet, grid: &ScheduleGrid) -> Result<()> {
+    let labels = grid.lang.labels();
+    let headers = [
+        labels.date,
+        labels.time,
+        labels.remaining_seats,
+        labels.status,
+        labels.special_session,
+        labels.details,
+        labels.fetched_at_utc,
+    ];
+
+    let mut bold = Format::new();
+    bold.set_bold();
+    let mut date_format = Format::new();
+    date_format.set_num_format("yyyy-mm-dd");
+    let mut fetched_format = Format::new();
+    fetched_format.set_num_format("yyyy-mm-dd hh:mm");
+
+    for (col, header) in (0..).zip(headers) {
+        sheet.write_string(0, col, header, Some(&bold))?;
+    }
+
+    let mut row = 0;
+    for session in grid.days.iter().flat_map(|d| &d.sessions) {
+        row += 1;
+        let local = session.time.local();
+        let fetched = session.fetched_at.to_offset(UtcOffset::UTC);
+
+        sheet.write_datetime(
+            row,
+            0,
+            &date_time(local.date(), Time::MIDNIGHT),
+            Some(&date_format),
+        )?;
+        sheet.write_string(row, 1, &grid.lang.time(local.time()), None)?;
+        sheet.write_number(row, 2, session.details.remaining_seats().into(), None)?;
+        let status = match session.details.availability() {
+            Availability::Open(_) => labels.open,
+            Availability::Full => labels.full,
+            Availability::Closed => labels.closed,
+        };
+        sheet.write_string(row, 3, status, None)?;
+        if session.details.special_session {
+            sheet.write_string(row, 4, labels.yes, None)?;
         }
+        sheet.write_string(row, 5, &session.details.summary(grid.lang), None)?;
+        sheet.write_datetime(
+            row,
+            6,
+            &date_time(fetched.date(), fetched.time()),
+            Some(&fetched_format),
+        )?;
+    }
+
+    sheet.autofilter(0, 0, row, headers.len() as u16 - 1)?;
+    sheet.freeze_panes(1, 0);
+    sheet.set_column(0, 4, 14.0, None)?;
+    sheet.set_column(5, 5, 40.0, None)?;
+    sheet.set_column(6, 6, 18.0, None)?;
 
-        Ok(())
+    Ok(())
+}
+
+fn date_time(date: Date, time: Time) -> DateTime {
+    DateTime::new(
+        date.year() as i16,
+        date.month() as i8,
+        date.day() as i8,
+        time.hour() as i8,
+        time.minute() as i8,
+        time.second().into(),
+    )
+}
+
+fn cell_format(colors: &ColorScale, cell: &Cell) -> Format {
+    let fill = colors.fill(cell.state);
+    let mut format = Format::new();
+    format
+        .set_bg_color(FormatColor::Custom(fill.as_u32()))
+        .set_font_color(FormatColor::Custom(fill.text_color().as_u32()))
+        .set_align(FormatAlignment::Center);
+    if matches!(colors.group_size(), Some(size) if cell.fits_group(size)) {
+        group_border(&mut format);
+    }
+
+    format
+}
+
+/// Thick blue border marking slots that have room for the whole group.
+fn group_border(format: &mut Format) -> &mut Format {
+    format
+        .set_border(FormatBorder::Thick)
+        .set_border_color(FormatColor::Custom(0x0d6efd))
+}
+
+/// Details that don't fit in the cell itself. If several sessions share a slot, or a session doesn't start
+/// right at the beginning of it, each one is listed with its own time and seat count.
+fn slot_comment(cell: &Cell, slot: Time, lang: Lang) -> String {
+    if !cell.shows_session_times(slot) {
+        return cell
+            .sessions
+            .first()
+            .map(|s| s.details.summary(lang))
+            .unwrap_or_default();
     }
+
+    let lines: Vec<_> = cell
+        .sessions
+        .iter()
+        .map(|s| {
+            let summary = s.details.summary(lang);
+            let mut line = format!(
+                "{}: {} {}",
+                lang.time(s.time.local().time()),
+                s.details.remaining_seats(),
+                lang.labels().seats
+            );
+            if !summary.is_empty() {
+                line.push_str(&format!(" ({})", summary));
+            }
+            line
+        })
+        .collect();
+
+    lines.join("\n")
 }
@@ -0,0 +1,155 @@
+use crate::{
+    data::{Day, OrdinanceType},
+    Temple,
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+use time::{macros::format_description, serde::rfc3339, Date, OffsetDateTime};
+use time_tz::TimeZone;
+
+use super::{
+    grid::{ScheduleGrid, ScheduleStats},
+    OutputOptions, OutputWriter,
+};
+
+/// One row per session, for loading into spreadsheets and other tools.
+pub struct CsvWriter;
+
+/// Every session along with the temple and totals, for scripts.
+pub struct JsonWriter;
+
+#[derive(Serialize)]
+struct SessionRow<'a> {
+    temple: &'a str,
+    date: Date,
+    time: String,
+    timezone: &'a str,
+    ordinance: String,
+    remaining_seats: u32,
+    #[serde(with = "rfc3339")]
+    fetched_at: OffsetDateTime,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScheduleExport<'a> {
+    temple: &'a Temple,
+    ordinance: String,
+    timezone: &'a str,
+    stats: &'a ScheduleStats,
+    days: &'a [Day],
+}
+
+impl OutputWriter for CsvWriter {
+    fn extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        _options: &OutputOptions,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(output);
+        let time_format = format_description!("[hour]:[minute]");
+
+        for session in grid.days.iter().flat_map(|d| &d.sessions) {
+            writer.serialize(SessionRow {
+                temple: &temple.name,
+                date: session.time.date(),
+                time: session.time.local().format(&time_format)?,
+                timezone: session.time.timezone().name(),
+                ordinance: OrdinanceType::Endowment.to_string(),
+                remaining_seats: session.details.remaining_seats(),
+                fetched_at: session.fetched_at,
+            })?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl OutputWriter for JsonWriter {
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        options: &OutputOptions,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        let export = ScheduleExport {
+            temple,
+            ordinance: OrdinanceType::Endowment.to_string(),
+            timezone: options.timezone.resolve(temple.timezone()).name(),
+            stats: &grid.stats,
+            days: &grid.days,
+        };
+        serde_json::to_writer_pretty(&mut *output, &export)?;
+        writeln!(output)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::tests::{day, temple},
+        timezone::DisplayTimezone,
+        visualize::{tests::options, write_output},
+    };
+    use time::macros::{date, time};
+    use time_tz::timezones::db::america::NEW_YORK;
+
+    #[test]
+    fn csv_times_name_their_timezone() {
+        let temple = temple(99, "Provo Utah Temple");
+        let days = [day(
+            &temple,
+            date!(2024 - 03 - 05),
+            &[(time!(22:30), Some(4))],
+        )];
+
+        let mut csv = vec![];
+        let options = options(DisplayTimezone::Named(NEW_YORK));
+        write_output(&days, &temple, &CsvWriter, &mut csv, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "temple,date,time,timezone,ordinance,remaining_seats,fetched_at\n\
+             Provo Utah Temple,2024-03-06,00:30,America/New_York,Endowment,4,1970-01-01T00:00:00Z\n"
+        );
+    }
+
+    #[test]
+    fn json_has_the_temple_totals_and_sessions() {
+        let temple = temple(99, "Provo Utah Temple");
+        let days = [day(
+            &temple,
+            date!(2024 - 03 - 05),
+            &[(time!(9:00), Some(4)), (time!(10:00), Some(0))],
+        )];
+
+        let mut json = vec![];
+        let options = options(DisplayTimezone::Temple);
+        write_output(&days, &temple, &JsonWriter, &mut json, &options).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["temple"]["name"], "Provo Utah Temple");
+        assert_eq!(json["timezone"], "America/Denver");
+        assert_eq!(json["stats"]["openSeats"], 4);
+        assert_eq!(json["stats"]["fullSessions"], 1);
+        assert_eq!(json["days"][0]["sessions"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            json["days"][0]["sessions"][0]["time"],
+            "2024-03-05T09:00:00-07:00"
+        );
+    }
+}
@@ -0,0 +1,598 @@
+use crate::data::{Availability, Day, Session};
+use crate::locale::Lang;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use time::{Date, Duration, Time};
+
+time::serde::format_description!(slot_format, Time, "[hour]:[minute]");
+
+/// A schedule laid out as a grid with a column per day and a row per time slot. This is computed once
+/// and then rendered by each of the output writers, so they all agree on what goes where.
+#[derive(Debug, Clone)]
+pub struct ScheduleGrid {
+    /// Start time of each row.
+    pub rows: Vec<Time>,
+
+    /// Date of each column.
+    pub columns: Vec<Date>,
+
+    /// Cells indexed by column, then row.
+    cells: Vec<Vec<Cell>>,
+
+    pub stats: ScheduleStats,
+
+    /// The days the grid was laid out from, for writers that export every session as is.
+    pub days: Vec<Day>,
+
+    /// Language for the row and column labels and anything else the writers show.
+    pub lang: Lang,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cell {
+    pub state: CellState,
+
+    /// Sessions starting in this slot, earliest first.
+    pub sessions: Vec<Session>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellState {
+    /// At least one session in the slot can still be booked, with this many seats left between them.
+    Open(u32),
+
+    /// Every session in the slot is booked up.
+    Full,
+
+    /// No sessions in this slot.
+    None,
+
+    /// Every session in the slot is closed.
+    Closed,
+}
+
+/// The columns of a grid that fall in one Sunday to Saturday week.
+#[derive(Debug, Clone)]
+pub struct Week {
+    pub start: Date,
+    pub columns: Vec<usize>,
+}
+
+/// A month laid out like a wall calendar, for months that have at least one day in the grid.
+#[derive(Debug, Clone)]
+pub struct CalendarMonth {
+    /// The first of the month.
+    pub start: Date,
+
+    /// Sunday to Saturday weeks covering the month. Days from the months before and after are None.
+    pub weeks: Vec<[Option<Date>; 7]>,
+}
+
+/// Totals for the whole schedule.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleStats {
+    pub total_sessions: usize,
+    pub open_sessions: usize,
+    pub full_sessions: usize,
+    pub closed_sessions: usize,
+    pub open_seats: u32,
+
+    /// Days that have sessions but none of them can be booked.
+    pub fully_booked_days: Vec<Date>,
+
+    /// The time slot with the most open seats across all days.
+    #[serde(with = "slot_format::option")]
+    pub best_slot: Option<Time>,
+}
+
+impl ScheduleGrid {
+    /// Lay out the days in a grid. If `slot_minutes` isn't given, the largest slot size (between 15
+    /// minutes and an hour) that gives every session its own row is used. Sessions that don't line up
+    /// with 15 minutes share a slot instead of making the grid hundreds of rows long.
+    pub fn new(schedules: &[Day], slot_minutes: Option<u16>, lang: Lang) -> Self {
+        let slot_minutes = slot_minutes.unwrap_or_else(|| default_slot_minutes(schedules));
+        let rows = time_slots(schedules, slot_minutes);
+        let columns: Vec<Date> = schedules.iter().map(|d| d.date).collect();
+
+        let cells: Vec<Vec<Cell>> = schedules
+            .iter()
+            .map(|day| {
+                let mut by_slot = sessions_by_slot(day, slot_minutes);
+                rows.iter()
+                    .map(|slot| Cell::new(by_slot.remove(slot).unwrap_or_default()))
+                    .collect()
+            })
+            .collect();
+
+        let mut grid = Self {
+            rows,
+            columns,
+            cells,
+            stats: ScheduleStats::default(),
+            days: schedules.to_vec(),
+            lang,
+        };
+        grid.stats = grid.compute_stats();
+        grid
+    }
+
+    /// Cells of a single day, from the earliest slot to the latest.
+    pub fn column(&self, column: usize) -> &[Cell] {
+        &self.cells[column]
+    }
+
+    pub fn row_label(&self, row: usize) -> String {
+        self.lang.time(self.rows[row])
+    }
+
+    pub fn column_label(&self, column: usize) -> String {
+        self.lang.day_label(self.columns[column])
+    }
+
+    /// The stats as a sentence, in the grid's language.
+    pub fn summary(&self) -> String {
+        self.stats.summary(self.lang)
+    }
+
+    /// Group the columns by the Sunday that starts their week.
+    pub fn weeks(&self) -> Vec<Week> {
+        let mut weeks: Vec<Week> = vec![];
+        for (column, date) in self.columns.iter().enumerate() {
+            let start = *date - Duration::days(date.weekday().number_days_from_sunday().into());
+            match weeks.last_mut() {
+                Some(week) if week.start == start => week.columns.push(column),
+                _ => weeks.push(Week {
+                    start,
+                    columns: vec![column],
+                }),
+            }
+        }
+
+        weeks
+    }
+
+    /// Group the days into calendar months.
+    pub fn months(&self) -> Vec<CalendarMonth> {
+        let mut starts: Vec<Date> = self
+            .columns
+            .iter()
+            .map(|d| d.replace_day(1).unwrap())
+            .collect();
+        starts.dedup();
+
+        starts
+            .into_iter()
+            .map(|start| {
+                let mut weeks = vec![];
+                let mut week = [None; 7];
+                let mut date = start;
+                while date.month() == start.month() {
+                    let weekday = date.weekday().number_days_from_sunday() as usize;
+                    week[weekday] = Some(date);
+                    if weekday == 6 {
+                        weeks.push(week);
+                        week = [None; 7];
+                    }
+                    date = date.next_day().unwrap();
+                }
+                if week.iter().any(Option::is_some) {
+                    weeks.push(week);
+                }
+
+                CalendarMonth { start, weeks }
+            })
+            .collect()
+    }
+
+    /// Column of the grid for a date, if the schedule has that day.
+    pub fn column_of(&self, date: Date) -> Option<usize> {
+        self.columns.iter().position(|d| *d == date)
+    }
+
+    /// Sessions of a day that can still be booked, earliest first.
+    pub fn open_sessions(&self, column: usize) -> Vec<&Session> {
+        let mut sessions: Vec<&Session> = self.days[column]
+            .sessions
+            .iter()
+            .filter(|s| matches!(s.details.availability(), Availability::Open(_)))
+            .collect();
+        sessions.sort_by_key(|s| s.time.local());
+        sessions
+    }
+
+    /// State of a whole day, the same way a slot's state comes from its sessions.
+    pub fn day_state(&self, column: usize) -> CellState {
+        let sessions: Vec<&Session> = self.days[column].sessions.iter().collect();
+        CellState::from_sessions(&sessions)
+    }
+
+    /// The most open seats in any one slot.
+    pub fn max_open_seats(&self) -> u32 {
+        self.cells
+            .iter()
+            .flatten()
+            .map(|cell| cell.state.open_seats())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Open seats in each time slot, across all days.
+    pub fn open_seats_by_row(&self) -> Vec<u32> {
+        (0..self.rows.len())
+            .map(|row| {
+                self.cells
+                    .iter()
+                    .map(|column| column[row].state.open_seats())
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn compute_stats(&self) -> ScheduleStats {
+        let sessions: Vec<&Session> = self
+            .cells
+            .iter()
+            .flatten()
+            .flat_map(|c| &c.sessions)
+            .collect();
+        let count = |availability: fn(&Availability) -> bool| {
+            sessions
+                .iter()
+                .filter(|s| availability(&s.details.availability()))
+                .count()
+        };
+
+        let fully_booked_days = self
+            .cells
+            .iter()
+            .zip(&self.columns)
+            .filter(|(column, _)| {
+                column.iter().any(|c| c.state != CellState::None)
+                    && column.iter().all(|c| c.state.open_seats() == 0)
+            })
+            .map(|(_, date)| *date)
+            .collect();
+
+        let best_slot = self
+            .open_seats_by_row()
+            .into_iter()
+            .zip(&self.rows)
+            .filter(|(seats, _)| *seats > 0)
+            .max_by_key(|(seats, _)| *seats)
+            .map(|(_, slot)| *slot);
+
+        ScheduleStats {
+            total_sessions: sessions.len(),
+            open_sessions: count(|a| matches!(a, Availability::Open(_))),
+            full_sessions: count(|a| *a == Availability::Full),
+            closed_sessions: count(|a| *a == Availability::Closed),
+            open_seats: sessions
+                .iter()
+                .map(|s| match s.details.availability() {
+                    Availability::Open(remaining) => remaining,
+                    _ => 0,
+                })
+                .sum(),
+            fully_booked_days,
+            best_slot,
+        }
+    }
+}
+
+impl Cell {
+    fn new(sessions: Vec<&Session>) -> Self {
+        Self {
+            state: CellState::from_sessions(&sessions),
+            sessions: sessions.into_iter().cloned().collect(),
+        }
+    }
+
+    /// Whether a single session in the slot has room for a group this big.
+    pub fn fits_group(&self, size: u32) -> bool {
+        self.sessions
+            .iter()
+            .any(|s| match s.details.availability() {
+                Availability::Open(remaining) => remaining >= size,
+                _ => false,
+            })
+    }
+
+    /// Whether each session's own start time needs to be shown, because there is more than one session
+    /// in the slot or the session doesn't start right at the beginning of it.
+    pub fn shows_session_times(&self, slot: Time) -> bool {
+        match self.sessions.as_slice() {
+            [session] => session.time.local().time() != slot,
+            [] => false,
+            _ => true,
+        }
+    }
+}
+
+impl CellState {
+    /// Open seats of all sessions in a slot are added up, and the slot is only closed if every session
+    /// in it is.
+    fn from_sessions(sessions: &[&Session]) -> Self {
+        if sessions.is_empty() {
+            return CellState::None;
+        }
+
+        let availabilities: Vec<_> = sessions.iter().map(|s| s.details.availability()).collect();
+        let open: u32 = availabilities
+            .iter()
+            .map(|a| match a {
+                Availability::Open(remaining) => *remaining,
+                _ => 0,
+            })
+            .sum();
+
+        if open > 0 {
+            CellState::Open(open)
+        } else if availabilities.iter().all(|a| *a == Availability::Closed) {
+            CellState::Closed
+        } else {
+            CellState::Full
+        }
+    }
+
+    pub fn open_seats(&self) -> u32 {
+        match self {
+            CellState::Open(seats) => *seats,
+            _ => 0,
+        }
+    }
+}
+
+impl ScheduleStats {
+    pub fn summary(&self, lang: Lang) -> String {
+        let mut summary = lang.session_totals(
+            self.open_seats,
+            self.open_sessions,
+            self.total_sessions,
+            self.full_sessions,
+            self.closed_sessions,
+        );
+
+        if let Some(best) = self.best_slot {
+            summary.push_str(". ");
+            summary.push_str(&lang.most_open_seats_at(best));
+        }
+
+        if !self.fully_booked_days.is_empty() {
+            let days: Vec<_> = self
+                .fully_booked_days
+                .iter()
+                .map(|d| lang.day_label(*d))
+                .collect();
+            summary.push_str(". ");
+            summary.push_str(&lang.fully_booked_days(&days.join(", ")));
+        }
+
+        summary
+    }
+}
+
+fn minutes_since_midnight(time: Time) -> u16 {
+    time.hour() as u16 * 60 + time.minute() as u16
+}
+
+/// The smallest slot size picked automatically. A single session at an odd minute would otherwise
+/// shrink every slot down to a minute or two.
+const MIN_SLOT_MINUTES: u16 = 15;
+
+fn default_slot_minutes(schedules: &[Day]) -> u16 {
+    fn gcd(a: u16, b: u16) -> u16 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    schedules
+        .iter()
+        .flat_map(|d| &d.sessions)
+        .map(|s| minutes_since_midnight(s.time.local().time()))
+        .fold(60, gcd)
+        .max(MIN_SLOT_MINUTES)
+}
+
+/// Start of the slot a time falls into. Slots line up with midnight.
+fn slot_start(time: Time, slot_minutes: u16) -> Time {
+    let minutes = minutes_since_midnight(time) / slot_minutes * slot_minutes;
+    Time::from_hms((minutes / 60) as u8, (minutes % 60) as u8, 0).unwrap()
+}
+
+/// Every slot from the earliest session of any day to the latest.
+fn time_slots(schedules: &[Day], slot_minutes: u16) -> Vec<Time> {
+    let used: BTreeSet<u16> = schedules
+        .iter()
+        .flat_map(|d| &d.sessions)
+        .map(|s| minutes_since_midnight(slot_start(s.time.local().time(), slot_minutes)))
+        .collect();
+
+    match (used.iter().next(), used.iter().next_back()) {
+        (Some(&first), Some(&last)) => (first..=last)
+            .step_by(slot_minutes as usize)
+            .map(|m| Time::from_hms((m / 60) as u8, (m % 60) as u8, 0).unwrap())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Sessions of a day grouped by the slot they fall into. A slot has more than one session only if the
+/// slot size is bigger than the gap between sessions.
+fn sessions_by_slot(day: &Day, slot_minutes: u16) -> HashMap<Time, Vec<&Session>> {
+    let mut slots: HashMap<Time, Vec<&Session>> = HashMap::new();
+    for session in &day.sessions {
+        slots
+            .entry(slot_start(session.time.local().time(), slot_minutes))
+            .or_default()
+            .push(session);
+    }
+
+    for sessions in slots.values_mut() {
+        sessions.sort_by_key(|s| s.time.local());
+    }
+
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{day, temple};
+    use time::macros::{date, time};
+
+    #[test]
+    fn every_session_gets_its_own_slot() {
+        let temple = temple(99, "Provo Utah Temple");
+        let date = date!(2024 - 03 - 05);
+        let days = [day(
+            &temple,
+            date,
+            &[
+                (time!(9:15), Some(3)),
+                (time!(10:30), Some(2)),
+                (time!(10:45), Some(1)),
+            ],
+        )];
+        assert_eq!(default_slot_minutes(&days), 15);
+
+        let grid = ScheduleGrid::new(&days, None, Lang::En);
+        assert_eq!(grid.rows.first(), Some(&time!(9:15)));
+        assert_eq!(grid.rows.last(), Some(&time!(10:45)));
+
+        let filled: Vec<(Time, CellState)> = grid
+            .rows
+            .iter()
+            .zip(grid.column(0))
+            .filter(|(_, cell)| !cell.sessions.is_empty())
+            .map(|(row, cell)| (*row, cell.state))
+            .collect();
+        assert_eq!(
+            filled,
+            [
+                (time!(9:15), CellState::Open(3)),
+                (time!(10:30), CellState::Open(2)),
+                (time!(10:45), CellState::Open(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn odd_minutes_share_a_slot() {
+        let temple = temple(99, "Provo Utah Temple");
+        let date = date!(2024 - 03 - 05);
+        let days = [day(
+            &temple,
+            date,
+            &[
+                (time!(9:00), Some(1)),
+                (time!(9:07), Some(2)),
+                (time!(20:00), Some(1)),
+            ],
+        )];
+        assert_eq!(default_slot_minutes(&days), 15);
+
+        let grid = ScheduleGrid::new(&days, None, Lang::En);
+        assert_eq!(grid.rows.len(), 45);
+        assert_eq!(grid.column(0)[0].sessions.len(), 2);
+        assert_eq!(grid.column(0)[0].state, CellState::Open(3));
+    }
+
+    #[test]
+    fn quarter_hours_use_fifteen_minute_slots() {
+        let temple = temple(99, "Provo Utah Temple");
+        let date = date!(2024 - 03 - 05);
+        let days = [day(
+            &temple,
+            date,
+            &[
+                (time!(9:00), Some(1)),
+                (time!(9:15), Some(1)),
+                (time!(9:45), Some(1)),
+            ],
+        )];
+        assert_eq!(default_slot_minutes(&days), 15);
+        assert_eq!(
+            time_slots(&days, 15),
+            [time!(9:00), time!(9:15), time!(9:30), time!(9:45)]
+        );
+    }
+
+    #[test]
+    fn slots_combine_their_sessions() {
+        let temple = temple(99, "Provo Utah Temple");
+        let date = date!(2024 - 03 - 05);
+        let days = [day(
+            &temple,
+            date,
+            &[
+                (time!(9:00), Some(3)),
+                (time!(9:30), Some(2)),
+                (time!(10:00), None),
+                (time!(10:30), Some(0)),
+                (time!(11:00), None),
+                (time!(11:30), None),
+            ],
+        )];
+
+        let grid = ScheduleGrid::new(&days, Some(60), Lang::En);
+        let states: Vec<CellState> = grid.column(0).iter().map(|c| c.state).collect();
+        assert_eq!(
+            states,
+            [CellState::Open(5), CellState::Full, CellState::Closed]
+        );
+        assert_eq!(grid.column(0)[0].sessions.len(), 2);
+        assert!(grid.column(0)[0].fits_group(3));
+        assert!(!grid.column(0)[0].fits_group(4));
+    }
+
+    #[test]
+    fn stats() {
+        let temple = temple(99, "Provo Utah Temple");
+        let days = [
+            day(
+                &temple,
+                date!(2024 - 03 - 05),
+                &[(time!(9:00), Some(0)), (time!(10:00), None)],
+            ),
+            day(
+                &temple,
+                date!(2024 - 03 - 06),
+                &[(time!(9:00), Some(4)), (time!(10:00), Some(1))],
+            ),
+            day(&temple, date!(2024 - 03 - 07), &[]),
+            day(
+                &temple,
+                date!(2024 - 03 - 08),
+                &[(time!(9:00), Some(0)), (time!(10:00), Some(2))],
+            ),
+        ];
+
+        let stats = ScheduleGrid::new(&days, None, Lang::En).stats;
+        assert_eq!(stats.total_sessions, 6);
+        assert_eq!(stats.open_sessions, 3);
+        assert_eq!(stats.full_sessions, 2);
+        assert_eq!(stats.closed_sessions, 1);
+        assert_eq!(stats.open_seats, 7);
+
+        // A day without any sessions isn't booked up, it's just closed.
+        assert_eq!(stats.fully_booked_days, [date!(2024 - 03 - 05)]);
+        assert_eq!(stats.best_slot, Some(time!(9:00)));
+    }
+
+    #[test]
+    fn no_best_slot_without_open_seats() {
+        let temple = temple(99, "Provo Utah Temple");
+        let days = [day(
+            &temple,
+            date!(2024 - 03 - 05),
+            &[(time!(9:00), Some(0))],
+        )];
+        let stats = ScheduleGrid::new(&days, None, Lang::En).stats;
+        assert_eq!(stats.best_slot, None);
+        assert_eq!(stats.fully_booked_days, [date!(2024 - 03 - 05)]);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::Temple;
+use anyhow::{anyhow, Result};
+use resvg::{tiny_skia, usvg};
+use std::{fmt::Write, io};
+
+use super::{
+    color::ColorScale,
+    grid::{CellState, ScheduleGrid},
+    markup::escape,
+    OutputOptions, OutputWriter,
+};
+
+/// Aim for an image about this wide, which fits a newsletter column or a phone screen without much
+/// scaling. Schedules with lots of days get narrower columns, up to a point.
+const TARGET_WIDTH: u32 = 1200;
+const ROW_LABEL_WIDTH: u32 = 80;
+const HEADER_HEIGHT: u32 = 110;
+const LEGEND_LINE_HEIGHT: u32 = 24;
+const PADDING: u32 = 20;
+const ROW_HEIGHT: u32 = 18;
+
+const FONT: &[u8] = include_bytes!("fonts/DejaVuSans.ttf");
+const BOLD_FONT: &[u8] = include_bytes!("fonts/DejaVuSans-Bold.ttf");
+const MIN_COLUMN_WIDTH: u32 = 30;
+const MAX_COLUMN_WIDTH: u32 = 90;
+
+/// PNGs are rendered at twice the SVG's size so they stay sharp on high density screens.
+const PNG_SCALE: f32 = 2.0;
+
+/// Empty slots are lighter than in the other outputs so the open ones stand out.
+const EMPTY: &str = "#f1f3f5";
+const TEXT: &str = "#212529";
+const GROUP: &str = "#0d6efd";
+
+pub struct SvgWriter;
+
+pub struct PngWriter;
+
+impl OutputWriter for SvgWriter {
+    fn extension(&self) -> &'static str {
+        "svg"
+    }
+
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        options: &OutputOptions,
+        output: &mut dyn io::Write,
+    ) -> Result<()> {
+        let colors = ColorScale::new(options, grid);
+        output.write_all(heatmap(grid, temple, &colors).as_bytes())?;
+        Ok(())
+    }
+}
+
+impl OutputWriter for PngWriter {
+    fn extension(&self) -> &'static str {
+        "png"
+    }
+
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        options: &OutputOptions,
+        output: &mut dyn io::Write,
+    ) -> Result<()> {
+        let colors = ColorScale::new(options, grid);
+        let mut svg_options = usvg::Options::default();
+        load_fonts(svg_options.fontdb_mut());
+        svg_options.fontdb_mut().load_system_fonts();
+        let tree = usvg::Tree::from_str(&heatmap(grid, temple, &colors), &svg_options)?;
+
+        let size = tree.size().to_int_size().scale_by(PNG_SCALE).unwrap();
+        let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+            .ok_or_else(|| anyhow!("Schedule is too large to render as an image"))?;
+        resvg::render(
+            &tree,
+            tiny_skia::Transform::from_scale(PNG_SCALE, PNG_SCALE),
+            &mut pixmap.as_mut(),
+        );
+        output.write_all(&pixmap.encode_png()?)?;
+
+        Ok(())
+    }
+}
+
+/// Load the bundled fonts and use them for sans-serif text. Without a font resvg leaves out all the
+/// text, so this makes sure the title, labels and legend show up even where the fonts named in the SVG
+/// aren't installed.
+fn load_fonts(fonts: &mut usvg::fontdb::Database) {
+    fonts.load_font_data(FONT.to_vec());
+    fonts.load_font_data(BOLD_FONT.to_vec());
+    fonts.set_sans_serif_family("DejaVu Sans");
+}
+
+/// Render the grid as an SVG heatmap, with open slots shaded darker the more seats they have left.
+fn heatmap(grid: &ScheduleGrid, temple: &Temple, colors: &ColorScale) -> String {
+    let columns = grid.columns.len() as u32;
+    let rows = grid.rows.len() as u32;
+    let column_width = ((TARGET_WIDTH - ROW_LABEL_WIDTH - 2 * PADDING) / columns.max(1))
+        .clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+    let width = ROW_LABEL_WIDTH + columns * column_width + 2 * PADDING;
+    let legend_top = HEADER_HEIGHT + rows * ROW_HEIGHT + PADDING;
+    let stats = wrap(&grid.summary(), (width - 2 * PADDING) / 7);
+    let (legend, legend_height) = legend(legend_top, width, grid, colors);
+    let stats_top = legend_top + legend_height + 18;
+    let height = stats_top + stats.len() as u32 * 18 + PADDING;
+
+    let mut svg = String::new();
+    write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"Helvetica, Arial, 'DejaVu Sans', sans-serif\" fill=\"{TEXT}\">
+<rect width=\"100%\" height=\"100%\" fill=\"#fff\"/>
+<text x=\"{PADDING}\" y=\"44\" font-size=\"26\" font-weight=\"bold\">{name}</text>
+<text x=\"{PADDING}\" y=\"70\" font-size=\"14\">{subtitle}</text>
+",
+        name = escape(&temple.name),
+        subtitle = escape(grid.lang.labels().open_endowment_seats),
+    )
+    .unwrap();
+
+    let font_size = if column_width < 40 { 10 } else { 12 };
+
+    for (c, date) in grid.columns.iter().enumerate() {
+        let x = ROW_LABEL_WIDTH + PADDING + c as u32 * column_width;
+        let center = x + column_width / 2;
+        write!(
+            svg,
+            "<text x=\"{center}\" y=\"{}\" font-size=\"{font_size}\" text-anchor=\"middle\" font-weight=\"bold\">{}</text>
+<text x=\"{center}\" y=\"{}\" font-size=\"{font_size}\" text-anchor=\"middle\">{}</text>
+",
+            HEADER_HEIGHT - 22,
+            escape(grid.lang.weekday_short(date.weekday())),
+            HEADER_HEIGHT - 8,
+            escape(&grid.lang.month_day(*date)),
+        )
+        .unwrap();
+
+        for (r, cell) in grid.column(c).iter().enumerate() {
+            let y = HEADER_HEIGHT + r as u32 * ROW_HEIGHT;
+            let fill = match cell.state {
+                CellState::None => EMPTY.to_string(),
+                state => colors.fill(state).hex(),
+            };
+            let label_color = colors.fill(cell.state).text_color().hex();
+            let label = match cell.state {
+                CellState::Open(seats) => seats.to_string(),
+                _ => String::new(),
+            };
+            writeln!(
+                svg,
+                "<rect x=\"{x}\" y=\"{y}\" width=\"{column_width}\" height=\"{ROW_HEIGHT}\" fill=\"{fill}\" stroke=\"#fff\"/>"
+            )
+            .unwrap();
+            if matches!(colors.group_size(), Some(size) if cell.fits_group(size)) {
+                writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{GROUP}\" stroke-width=\"3\"/>",
+                    x + 2,
+                    y + 2,
+                    column_width - 4,
+                    ROW_HEIGHT - 4
+                )
+                .unwrap();
+            }
+            if !label.is_empty() {
+                writeln!(
+                    svg,
+                    "<text x=\"{center}\" y=\"{}\" font-size=\"11\" text-anchor=\"middle\" fill=\"{label_color}\">{label}</text>",
+                    y + ROW_HEIGHT - 5
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    for r in 0..grid.rows.len() {
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"11\" text-anchor=\"end\">{}</text>",
+            PADDING + ROW_LABEL_WIDTH - 8,
+            HEADER_HEIGHT + r as u32 * ROW_HEIGHT + ROW_HEIGHT - 5,
+            grid.row_label(r)
+        )
+        .unwrap();
+    }
+
+    svg.push_str(&legend);
+
+    for (i, line) in stats.iter().enumerate() {
+        writeln!(
+            svg,
+            "<text x=\"{PADDING}\" y=\"{}\" font-size=\"12\">{}</text>",
+            stats_top + i as u32 * 18,
+            escape(line)
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// A swatch for each color used, plus the outline marking slots that fit the group if one was given.
+/// Returns the SVG along with how tall it is, since the legend wraps if the image is narrow.
+fn legend(top: u32, width: u32, grid: &ScheduleGrid, colors: &ColorScale) -> (String, u32) {
+    let mut svg = String::new();
+    let mut entries: Vec<(String, String, &str)> = colors
+        .legend()
+        .into_iter()
+        .map(|entry| {
+            let fill = match entry.state {
+                CellState::None => EMPTY.to_string(),
+                _ => entry.fill.hex(),
+            };
+            (entry.label, fill, "#adb5bd")
+        })
+        .collect();
+    if let Some(size) = colors.group_size() {
+        entries.push((grid.lang.room_for(size), "#fff".to_string(), GROUP));
+    }
+
+    let (mut x, mut y) = (PADDING, top);
+    for (label, fill, stroke) in entries {
+        let entry_width = 20 + label.chars().count() as u32 * 7 + 20;
+        if x > PADDING && x + entry_width > width - PADDING {
+            x = PADDING;
+            y += LEGEND_LINE_HEIGHT;
+        }
+        writeln!(
+            svg,
+            "<rect x=\"{x}\" y=\"{}\" width=\"14\" height=\"14\" fill=\"{fill}\" stroke=\"{stroke}\" stroke-width=\"2\"/>
+<text x=\"{}\" y=\"{}\" font-size=\"12\">{}</text>",
+            y,
+            x + 20,
+            y + 12,
+            escape(&label),
+        )
+        .unwrap();
+        x += entry_width;
+    }
+
+    (svg, y + 14 - top)
+}
+
+/// Break text into lines of at most `max_chars` characters, without splitting words.
+fn wrap(text: &str, max_chars: u32) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if (line.len() + word.len()) < max_chars as usize => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::SessionsJSON, locale::Lang, timezone::DisplayTimezone, visualize::tests::options,
+    };
+    use time::OffsetDateTime;
+
+    /// Text elements that made it into the tree, which only happens if a font was found for them.
+    fn text_nodes(group: &usvg::Group) -> usize {
+        group
+            .children()
+            .iter()
+            .map(|node| match node {
+                usvg::Node::Text(_) => 1,
+                usvg::Node::Group(group) => text_nodes(group),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn text_renders_with_only_the_bundled_fonts() {
+        let temple = crate::data::tests::temple(99, "Provo Utah Temple");
+        let sessions: SessionsJSON = serde_json::from_value(serde_json::json!({
+            "sessionList": [
+                { "time": "2024-03-05T09:00:00", "details": { "remainingOnlineSeatsAvailable": 4 } },
+                { "time": "2024-03-05T10:00:00", "details": { "remainingOnlineSeatsAvailable": 0 } },
+            ]
+        }))
+        .unwrap();
+        let day = crate::data::Day {
+            date: time::macros::date!(2024 - 03 - 05),
+            sessions: sessions
+                .session_list
+                .into_iter()
+                .map(|s| s.into_session(temple.timezone(), OffsetDateTime::UNIX_EPOCH))
+                .collect(),
+        };
+        let grid = ScheduleGrid::new(&[day], None, Lang::En);
+        let options = options(DisplayTimezone::Temple);
+        let svg = heatmap(&grid, &temple, &ColorScale::new(&options, &grid));
+
+        let without_fonts = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+        assert_eq!(text_nodes(without_fonts.root()), 0);
+
+        let mut svg_options = usvg::Options::default();
+        load_fonts(svg_options.fontdb_mut());
+        let tree = usvg::Tree::from_str(&svg, &svg_options).unwrap();
+        assert_eq!(text_nodes(tree.root()), svg.matches("<text").count());
+    }
+}
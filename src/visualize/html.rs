@@ -1,135 +1,460 @@
-use crate::{data::Day, Temple};
-use anyhow::Result;
-use std::collections::HashMap;
-use std::io::Write;
-use time::macros::format_description;
+use crate::{
+    data::{Availability, Session},
+    locale::{Labels, Lang},
+    Temple,
+};
+use anyhow::{Context, Result};
+use minijinja::Environment;
+use serde::Serialize;
+use std::{io::Write, path::PathBuf};
+use time::{Date, Weekday};
+use time_tz::{Offset, OffsetDateTimeExt, TimeZone};
 
-use super::OutputWriter;
+use super::{
+    color::ColorScale,
+    grid::{CellState, ScheduleGrid, ScheduleStats},
+    Layout, OutputOptions, OutputWriter,
+};
 
-pub struct HTMLWriter;
+#[derive(Default)]
+pub struct HTMLWriter {
+    /// Template to render the page with instead of the built in one.
+    pub template: Option<PathBuf>,
+}
+
+/// Used unless another template is given. Custom templates can extend it by name and fill in its
+/// `head`, `header`, `notes` and `footer` blocks instead of starting from scratch.
+const DEFAULT_TEMPLATE: &str = include_str!("templates/schedule.html");
+const DEFAULT_TEMPLATE_NAME: &str = "default.html";
+
+/// Everything a template can use. The grid is laid out row by row, the way an HTML table is written, and
+/// the calendar month by month and week by week.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Report<'a> {
+    temple: &'a Temple,
+    timezone: &'a str,
+
+    /// Language tag for the page, like en or es.
+    lang: &'static str,
+
+    /// Fixed labels in the page's language.
+    text: &'static Labels,
+
+    stats: &'a ScheduleStats,
+
+    /// The stats as a sentence.
+    summary: String,
+
+    legend: Vec<LegendSwatch>,
+    group_size: Option<u32>,
+    weekdays: Vec<WeekdayFilter>,
+
+    /// Either grid or calendar.
+    layout: &'static str,
+
+    columns: Vec<ColumnView>,
+    rows: Vec<RowView>,
+
+    /// Names for the calendar's columns, Sunday first.
+    weekday_names: Vec<&'static str>,
+
+    months: Vec<MonthView>,
+}
+
+#[derive(Serialize)]
+struct LegendSwatch {
+    label: String,
+    background: String,
+    group: bool,
+}
+
+#[derive(Serialize)]
+struct WeekdayFilter {
+    value: u8,
+    label: String,
+}
+
+#[derive(Serialize)]
+struct ColumnView {
+    label: String,
+    date: Date,
+
+    /// Days since Sunday.
+    weekday: u8,
+}
+
+#[derive(Serialize)]
+struct RowView {
+    label: String,
+
+    /// Start of the slot in minutes since midnight.
+    minutes: u16,
+
+    cells: Vec<CellView>,
+}
+
+#[derive(Serialize)]
+struct CellView {
+    /// One of open, full, closed or none.
+    state: &'static str,
+    weekday: u8,
+    background: String,
+    color: String,
+
+    /// Whether a session in the slot has room for the whole group.
+    group: bool,
+
+    /// The most open seats in any one session in the slot.
+    seats: u32,
+
+    tooltip: String,
+    sessions: Vec<SessionView>,
+}
+
+#[derive(Serialize)]
+struct MonthView {
+    label: String,
+    weeks: Vec<Vec<DayView>>,
+}
+
+#[derive(Serialize)]
+struct DayView {
+    /// Day of the month, or None for the days of the months before and after.
+    day: Option<u8>,
+    weekday: u8,
+
+    /// One of open, full, closed or none, like a cell in the grid.
+    state: &'static str,
+
+    /// Full or Closed for days that had sessions but none that can be booked.
+    note: Option<String>,
+
+    sessions: Vec<CalendarSessionView>,
+}
+
+/// A session that can still be booked, shown in its day of the calendar.
+#[derive(Serialize)]
+struct CalendarSessionView {
+    time: String,
+
+    /// Start in minutes since midnight.
+    minutes: u16,
+
+    seats: u32,
+    background: String,
+    color: String,
+    group: bool,
+    special: bool,
+    tooltip: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionView {
+    /// Start time, only set if it differs from the slot's.
+    time: Option<String>,
+
+    /// Open seats, or Full or Closed in the page's language.
+    seats: String,
+
+    fill_percentage: Option<u32>,
+
+    /// The fill percentage as shown, like 75% full.
+    fill: Option<String>,
+
+    language: Option<String>,
+    special: bool,
+}
 
 impl OutputWriter for HTMLWriter {
-    fn write_output(schedules: &[Day], temple: &Temple, filename: &str) -> Result<()> {
-        let mut output = std::fs::File::create(format!("{filename}.html"))?;
-
-        const START_HOUR: u8 = 5;
-        const END_HOUR: u8 = 20;
-
-        let prefix = format!(
-            "<!DOCTYPE html>
-        <html>
-        
-        <head>
-        <link href=\"https://cdn.jsdelivr.net/npm/bootstrap@5.1.3/dist/css/bootstrap.min.css\" rel=\"stylesheet\" integrity=\"sha384-1BmE4kWBq78iYhFldvKuhfTAU6auU8tT94WrHftjDbrCEXSU1oBoqyl2QvZ6jIW3\" crossorigin=\"anonymous\">
-            <style>
-                .grid-container {{
-                    display: grid;
-                    grid-template-columns: repeat({num_columns}, 1fr);
-                    grid-template-rows: repeat({num_rows}, 1fr);
-                    grid-auto-flow: column;
-                    width: 300%;
-                    height: 50%;
-                }}
-    
-                body {{
-                    padding: 20px;
-                }}
-        
-                .grid-item {{
-                    border: 1px solid rgba(0, 0, 0, 0.8);
-                    padding: 5px;
-                    font-size: 15px;
-                    text-align: center;
-                }}
-        
-                .header-item {{
-                    grid-row-start: 0;
-                }}
-        
-                .item1 {{
-                    background: LightSkyBlue;
-                }}
-            </style>
-        </head>
-        
-        <body>
-            <h1>{temple_name}</h1>
-            <p>Available slots for endowment</p>
-            <div class=\"grid-container\">",
-            num_columns = schedules.len() + 1,
-            num_rows = (END_HOUR - START_HOUR) * 2 + 1,
-            temple_name = temple.name
-        );
-
-        writeln!(output, "{}", prefix)?;
-
-        writeln!(output, "<div class=\"grid-item\"></div>")?; // Blank in corner
-
-        for hour in START_HOUR..END_HOUR {
-            for minutes in [0, 30] {
-                if hour <= 12 {
-                    writeln!(
-                        output,
-                        "<div class=\"grid-item\">{}:{:02} AM</div>",
-                        hour, minutes
-                    )?;
-                } else {
-                    writeln!(
-                        output,
-                        "<div class=\"grid-item\">{}:{:02} PM</div>",
-                        hour - 12,
-                        minutes
-                    )?;
-                }
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        options: &OutputOptions,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        let colors = ColorScale::new(options, grid);
+
+        // Templates are named with .html so everything they output gets escaped.
+        let mut env = Environment::new();
+        env.add_template(DEFAULT_TEMPLATE_NAME, DEFAULT_TEMPLATE)?;
+        let name = match &self.template {
+            Some(path) => {
+                let source = std::fs::read_to_string(path)
+                    .with_context(|| format!("Unable to read template {}", path.display()))?;
+                env.add_template_owned("custom.html", source)
+                    .with_context(|| format!("Invalid template {}", path.display()))?;
+                "custom.html"
             }
-        }
+            None => DEFAULT_TEMPLATE_NAME,
+        };
+
+        let report = Report {
+            temple,
+            timezone: options.timezone.resolve(temple.timezone()).name(),
+            lang: grid.lang.code(),
+            text: grid.lang.labels(),
+            stats: &grid.stats,
+            summary: grid.summary(),
+            legend: legend(&colors, grid.lang),
+            group_size: colors.group_size(),
+            weekdays: weekday_filters(grid),
+            layout: match options.layout {
+                Layout::Grid => "grid",
+                Layout::Calendar => "calendar",
+            },
+            columns: (0..grid.columns.len())
+                .map(|col| ColumnView {
+                    label: grid.column_label(col),
+                    date: grid.columns[col],
+                    weekday: grid.columns[col].weekday().number_days_from_sunday(),
+                })
+                .collect(),
+            rows: (0..grid.rows.len())
+                .map(|row| row_view(grid, &colors, row))
+                .collect(),
+            weekday_names: (0..7)
+                .map(|days| grid.lang.weekday_short(Weekday::Sunday.nth_next(days)))
+                .collect(),
+            months: month_views(grid, &colors),
+        };
+
+        let html = env.get_template(name)?.render(&report)?;
+        writeln!(output, "{}", html)?;
+
+        Ok(())
+    }
+}
+
+fn row_view(grid: &ScheduleGrid, colors: &ColorScale, row: usize) -> RowView {
+    let slot = grid.rows[row];
+    let cells = (0..grid.columns.len())
+        .map(|col| {
+            let cell = &grid.column(col)[row];
+            let fill = colors.fill(cell.state);
+            let show_times = cell.shows_session_times(slot);
 
-        let date_format = format_description!("[weekday repr:short] [month repr:short] [day]");
-        for day in schedules {
-            writeln!(
-                output,
-                "<div class=\"grid-item\">{}</div>",
-                day.date.format(date_format).unwrap()
-            )?;
-
-            let hour_counts: HashMap<(u8, u8), u32> = day
-                .sessions
-                .session_list
+            CellView {
+                state: state_name(cell.state),
+                weekday: grid.columns[col].weekday().number_days_from_sunday(),
+                background: fill.hex(),
+                color: fill.text_color().hex(),
+                group: matches!(colors.group_size(), Some(size) if cell.fits_group(size)),
+                seats: cell
+                    .sessions
+                    .iter()
+                    .map(|s| match s.details.availability() {
+                        Availability::Open(remaining) => remaining,
+                        _ => 0,
+                    })
+                    .max()
+                    .unwrap_or(0),
+                tooltip: tooltip(&cell.sessions, grid.lang),
+                sessions: cell
+                    .sessions
+                    .iter()
+                    .map(|s| session_view(s, show_times, grid.lang))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    RowView {
+        label: grid.row_label(row),
+        minutes: slot.hour() as u16 * 60 + slot.minute() as u16,
+        cells,
+    }
+}
+
+fn month_views(grid: &ScheduleGrid, colors: &ColorScale) -> Vec<MonthView> {
+    grid.months()
+        .into_iter()
+        .map(|month| MonthView {
+            label: grid.lang.month_year(month.start),
+            weeks: month
+                .weeks
                 .iter()
-                .map(|s| {
-                    (
-                        (s.time.hour(), s.time.minute()),
-                        if s.details.remaining_online_seats_available < 0 {
-                            0
-                        } else {
-                            s.details.remaining_online_seats_available
-                        } as u32,
-                    )
+                .map(|week| {
+                    week.iter()
+                        .enumerate()
+                        .map(|(weekday, date)| day_view(grid, colors, weekday as u8, *date))
+                        .collect()
                 })
-                .collect();
-            for hour in START_HOUR..END_HOUR {
-                for minutes in [0, 30] {
-                    match hour_counts.get(&(hour, minutes)) {
-                        Some(&remaining) => {
-                            let background_color = if remaining > 0 {
-                                "bg-success"
-                            } else {
-                                "bg-danger"
-                            };
-                            writeln!(output, "<div class=\"grid-item text-white {background_color}\">{remaining}</div>")?
-                        }
-                        None => writeln!(output, "<div class=\"grid-item bg-secondary\"></div>")?,
-                    }
+                .collect(),
+        })
+        .collect()
+}
+
+fn day_view(grid: &ScheduleGrid, colors: &ColorScale, weekday: u8, date: Option<Date>) -> DayView {
+    let column = date.and_then(|date| grid.column_of(date));
+    let state = column.map_or(CellState::None, |column| grid.day_state(column));
+    let labels = grid.lang.labels();
+
+    DayView {
+        day: date.map(|date| date.day()),
+        weekday,
+        state: state_name(state),
+        note: match state {
+            CellState::Full => Some(labels.full.to_string()),
+            CellState::Closed => Some(labels.closed.to_string()),
+            _ => None,
+        },
+        sessions: column
+            .map(|column| grid.open_sessions(column))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|session| {
+                let seats = session.details.remaining_seats();
+                let fill = colors.open(seats);
+                let time = session.time.local().time();
+                CalendarSessionView {
+                    time: grid.lang.time(time),
+                    minutes: time.hour() as u16 * 60 + time.minute() as u16,
+                    seats,
+                    background: fill.hex(),
+                    color: fill.text_color().hex(),
+                    group: matches!(colors.group_size(), Some(size) if seats >= size),
+                    special: session.details.special_session,
+                    tooltip: tooltip(std::slice::from_ref(session), grid.lang),
                 }
-            }
+            })
+            .collect(),
+    }
+}
+
+fn state_name(state: CellState) -> &'static str {
+    match state {
+        CellState::Open(_) => "open",
+        CellState::Full => "full",
+        CellState::Closed => "closed",
+        CellState::None => "none",
+    }
+}
+
+/// A swatch for each color used, plus the outline marking slots that fit the group if one was given.
+fn legend(colors: &ColorScale, lang: Lang) -> Vec<LegendSwatch> {
+    let mut swatches: Vec<_> = colors
+        .legend()
+        .into_iter()
+        .map(|entry| LegendSwatch {
+            label: entry.label,
+            background: entry.fill.hex(),
+            group: false,
+        })
+        .collect();
+    if let Some(size) = colors.group_size() {
+        swatches.push(LegendSwatch {
+            label: lang.room_for(size),
+            background: "transparent".to_string(),
+            group: true,
+        });
+    }
+
+    swatches
+}
+
+/// A checkbox for each day of the week in the schedule, Sunday first.
+fn weekday_filters(grid: &ScheduleGrid) -> Vec<WeekdayFilter> {
+    let mut weekdays: Vec<Weekday> = vec![];
+    for date in &grid.columns {
+        if !weekdays.contains(&date.weekday()) {
+            weekdays.push(date.weekday());
         }
+    }
+    weekdays.sort_by_key(|w| w.number_days_from_sunday());
+
+    weekdays
+        .iter()
+        .map(|weekday| WeekdayFilter {
+            value: weekday.number_days_from_sunday(),
+            label: grid.lang.weekday_short(*weekday).to_string(),
+        })
+        .collect()
+}
 
-        let postfix = "       </div>
-        </body>
-        
-        </html>";
-        writeln!(output, "{}", postfix)?;
+/// Every session with its seats and details, and when the numbers were fetched.
+fn tooltip(sessions: &[Session], lang: Lang) -> String {
+    let mut lines = vec![];
+    for session in sessions {
+        let seats = match session.details.availability() {
+            Availability::Open(remaining) => lang.open_seats(remaining),
+            Availability::Full => lang.labels().full.to_string(),
+            Availability::Closed => lang.labels().closed.to_string(),
+        };
+        lines.push(format!(
+            "{}: {}",
+            lang.time(session.time.local().time()),
+            seats
+        ));
 
-        Ok(())
+        let summary = session.details.summary(lang);
+        if !summary.is_empty() {
+            lines.push(summary);
+        }
+    }
+
+    // Sessions in a cell are on the same day, so they were all fetched together.
+    if let Some(session) = sessions.first() {
+        let timezone = session.time.timezone();
+        let fetched = session.fetched_at.to_timezone(timezone);
+        lines.push(lang.fetched(fetched, timezone.get_offset_utc(&fetched).name()));
+    }
+
+    lines.join("\n")
+}
+
+/// Seat count for a session, with its start time if needed, fill percentage, language and special
+/// session marker.
+fn session_view(session: &Session, show_time: bool, lang: Lang) -> SessionView {
+    let details = &session.details;
+    SessionView {
+        time: show_time.then(|| lang.time(session.time.local().time())),
+        seats: match details.availability() {
+            Availability::Open(remaining) => remaining.to_string(),
+            Availability::Full => lang.labels().full.to_string(),
+            Availability::Closed => lang.labels().closed.to_string(),
+        },
+        fill_percentage: details.fill_percentage(),
+        fill: details
+            .fill_percentage()
+            .map(|fill| lang.percent_full(fill)),
+        language: details.language.clone(),
+        special: details.special_session,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::tests::{day, temple},
+        timezone::DisplayTimezone,
+        visualize::{tests::options, write_output},
+    };
+    use time::macros::{date, time};
+
+    #[test]
+    fn renders_an_escaped_page() {
+        let temple = temple(99, "Provo <Utah> & Orem Temple");
+        let days = [day(
+            &temple,
+            date!(2024 - 03 - 05),
+            &[(time!(9:00), Some(4))],
+        )];
+
+        let mut html = vec![];
+        let options = options(DisplayTimezone::Temple);
+        write_output(&days, &temple, &HTMLWriter::default(), &mut html, &options).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Provo &lt;Utah&gt; &amp; Orem Temple</h1>"));
+        assert!(!html.contains("<Utah>"));
+        assert!(html.contains("9:00"));
     }
 }
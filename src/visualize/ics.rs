@@ -0,0 +1,386 @@
+use crate::{
+    data::{AppointmentJSON, AppointmentStatus, Availability, OrdinanceType, Session},
+    locale::Lang,
+    timezone::DisplayTimezone,
+    Temple,
+};
+use anyhow::Result;
+use std::{collections::BTreeMap, io::Write};
+use time::{macros::format_description, Duration, OffsetDateTime, UtcOffset};
+use time_tz::{Offset, TimeZone, Tz};
+
+use super::{grid::ScheduleGrid, OutputOptions, OutputWriter};
+
+/// The API doesn't say how long sessions are, but an endowment session takes about this long.
+const SESSION_LENGTH: Duration = Duration::minutes(90);
+
+pub struct IcsWriter {
+    /// Sessions with fewer open seats than this are left out.
+    pub min_seats: u32,
+}
+
+impl Default for IcsWriter {
+    fn default() -> Self {
+        Self { min_seats: 1 }
+    }
+}
+
+impl OutputWriter for IcsWriter {
+    fn extension(&self) -> &'static str {
+        "ics"
+    }
+
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        _options: &OutputOptions,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        // Events always carry the temple's timezone. Calendar apps convert them for whoever imports them.
+        let timezone = temple.timezone();
+        let sessions: Vec<Session> = grid
+            .days
+            .iter()
+            .flat_map(|d| &d.sessions)
+            .filter(|s| match s.details.availability() {
+                Availability::Open(seats) => seats >= self.min_seats,
+                _ => false,
+            })
+            .map(|s| Session {
+                time: s.time.to_timezone(timezone),
+                ..s.clone()
+            })
+            .collect();
+
+        let mut lines = calendar(&grid.lang.calendar_name(&temple.name));
+        lines.push(format!("X-WR-TIMEZONE:{}", timezone.name()));
+
+        let first = sessions.iter().map(|s| s.time.local()).min();
+        let last = sessions.iter().map(|s| s.time.local()).max();
+        if let (Some(first), Some(last)) = (first, last) {
+            lines.extend(vtimezone(
+                timezone,
+                first - Duration::days(1),
+                last + Duration::days(1),
+            ));
+        }
+
+        for session in &sessions {
+            lines.extend(event(session, temple, grid.lang));
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        write_lines(&lines, output)
+    }
+}
+
+/// Write appointments as events, each in the timezone of its temple.
+pub fn write_appointments(
+    appointments: &[AppointmentJSON],
+    lang: Lang,
+    output: &mut dyn Write,
+) -> Result<()> {
+    let mut lines = calendar(lang.appointments_calendar());
+
+    // Every timezone used needs a definition covering the appointments in it.
+    let mut timezones: BTreeMap<&str, (&Tz, OffsetDateTime, OffsetDateTime)> = BTreeMap::new();
+    for appointment in appointments {
+        let time = appointment.date_time();
+        let start = time.local();
+        timezones
+            .entry(time.timezone().name())
+            .and_modify(|(_, first, last)| {
+                *first = (*first).min(start);
+                *last = (*last).max(start);
+            })
+            .or_insert((time.timezone(), start, start));
+    }
+    for (timezone, first, last) in timezones.into_values() {
+        lines.extend(vtimezone(
+            timezone,
+            first - Duration::days(1),
+            last + Duration::days(1),
+        ));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    for appointment in appointments {
+        lines.extend(appointment_event(appointment, now, lang));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    write_lines(&lines, output)
+}
+
+/// The start of a calendar, up to where timezones and events go.
+fn calendar(name: &str) -> Vec<String> {
+    vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//temples//Temple schedule//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ]
+}
+
+fn write_lines(lines: &[String], output: &mut dyn Write) -> Result<()> {
+    for line in lines {
+        write!(output, "{}\r\n", fold(line))?;
+    }
+    Ok(())
+}
+
+fn event(session: &Session, temple: &Temple, lang: Lang) -> Vec<String> {
+    let local_format = format_description!("[year][month][day]T[hour][minute][second]");
+    let utc_format = format_description!("[year][month][day]T[hour][minute][second]Z");
+
+    let start = session.time.local();
+    let end = start + SESSION_LENGTH;
+    let seats = session.details.remaining_seats();
+    let tzid = session.time.timezone().name();
+
+    let mut description = lang.seats_as_of(seats, session.fetched_at.to_offset(UtcOffset::UTC));
+    let summary = session.details.summary(lang);
+    if !summary.is_empty() {
+        description.push_str(&format!(" {}.", summary));
+    }
+
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!(
+            "UID:{}-{}@temples",
+            temple.temple_org_id,
+            start.to_offset(UtcOffset::UTC).format(&utc_format).unwrap()
+        ),
+        format!(
+            "DTSTAMP:{}",
+            session
+                .fetched_at
+                .to_offset(UtcOffset::UTC)
+                .format(&utc_format)
+                .unwrap()
+        ),
+        format!(
+            "DTSTART;TZID={}:{}",
+            tzid,
+            start.format(&local_format).unwrap()
+        ),
+        format!("DTEND;TZID={}:{}", tzid, end.format(&local_format).unwrap()),
+        format!("SUMMARY:{}", escape(&lang.session_event(seats))),
+        format!(
+            "LOCATION:{}",
+            escape(&format!(
+                "{}, {}, {}",
+                temple.name, temple.location, temple.country
+            ))
+        ),
+        format!("DESCRIPTION:{}", escape(&description)),
+        "STATUS:TENTATIVE".to_string(),
+        "TRANSP:TRANSPARENT".to_string(),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+fn appointment_event(
+    appointment: &AppointmentJSON,
+    now: OffsetDateTime,
+    lang: Lang,
+) -> Vec<String> {
+    let local_format = format_description!("[year][month][day]T[hour][minute][second]");
+    let utc_format = format_description!("[year][month][day]T[hour][minute][second]Z");
+
+    let time = appointment.date_time();
+    let start = time.local();
+    let end = start + appointment_length(appointment.ordinance_type());
+    let tzid = time.timezone().name();
+    let temple = appointment.temple_display_name();
+
+    // Confirmation numbers are stable, so importing again updates events instead of duplicating them.
+    let uid = match &appointment.confirmation_number {
+        Some(confirmation) => format!("{}@temples", confirmation),
+        None => format!(
+            "{}-{}-{}@temples",
+            appointment.temple_org_id.unwrap_or_default(),
+            appointment.ordinance_name(Lang::En),
+            start.to_offset(UtcOffset::UTC).format(&utc_format).unwrap()
+        ),
+    };
+    let status = match appointment.appointment_status {
+        AppointmentStatus::Cancelled => "CANCELLED",
+        AppointmentStatus::Booked | AppointmentStatus::Completed => "CONFIRMED",
+        AppointmentStatus::Unknown => "TENTATIVE",
+    };
+
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", escape(&uid)),
+        format!("DTSTAMP:{}", now.format(&utc_format).unwrap()),
+        format!(
+            "DTSTART;TZID={}:{}",
+            tzid,
+            start.format(&local_format).unwrap()
+        ),
+        format!("DTEND;TZID={}:{}", tzid, end.format(&local_format).unwrap()),
+        format!(
+            "SUMMARY:{}",
+            escape(&lang.appointment_event(&appointment.ordinance_name(lang), &temple))
+        ),
+        format!("LOCATION:{}", escape(&temple)),
+        format!(
+            "DESCRIPTION:{}",
+            escape(
+                &appointment
+                    .display_in(DisplayTimezone::Temple, lang)
+                    .to_string()
+            )
+        ),
+        format!("STATUS:{}", status),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+/// Roughly how long each ordinance takes, since appointments only say when they start.
+fn appointment_length(ordinance: Option<OrdinanceType>) -> Duration {
+    match ordinance {
+        Some(OrdinanceType::Endowment) => SESSION_LENGTH,
+        Some(OrdinanceType::Baptism | OrdinanceType::Initiatory | OrdinanceType::Sealing)
+        | None => Duration::hours(1),
+    }
+}
+
+/// Describe every offset change of the timezone between `start` and `end`. Calendar apps need this to
+/// interpret the TZID on each event, and listing only the changes we need avoids having to turn the
+/// timezone database back into recurrence rules.
+fn vtimezone(timezone: &Tz, start: OffsetDateTime, end: OffsetDateTime) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VTIMEZONE".to_string(),
+        format!("TZID:{}", timezone.name()),
+    ];
+
+    let mut offset = timezone.get_offset_utc(&start);
+    lines.extend(observance(&offset, &offset, start));
+
+    let mut time = start;
+    while time < end {
+        let next = time + Duration::hours(1);
+        let next_offset = timezone.get_offset_utc(&next);
+        if next_offset.to_utc() != offset.to_utc() {
+            // Changes don't always happen on the hour, so find the exact minute.
+            let mut change = time;
+            while timezone.get_offset_utc(&change).to_utc() == offset.to_utc() {
+                change += Duration::minutes(1);
+            }
+            lines.extend(observance(&offset, &next_offset, change));
+            offset = next_offset;
+        }
+        time = next;
+    }
+
+    lines.push("END:VTIMEZONE".to_string());
+    lines
+}
+
+/// A period starting at `at` where the timezone uses the offset `to`.
+fn observance(from: &impl Offset, to: &impl Offset, at: OffsetDateTime) -> Vec<String> {
+    let local_format = format_description!("[year][month][day]T[hour][minute][second]");
+    let offset_format = format_description!("[offset_hour sign:mandatory][offset_minute]");
+    let kind = if to.is_dst() { "DAYLIGHT" } else { "STANDARD" };
+
+    vec![
+        format!("BEGIN:{}", kind),
+        format!(
+            "DTSTART:{}",
+            at.to_offset(from.to_utc()).format(&local_format).unwrap()
+        ),
+        format!(
+            "TZOFFSETFROM:{}",
+            from.to_utc().format(&offset_format).unwrap()
+        ),
+        format!("TZOFFSETTO:{}", to.to_utc().format(&offset_format).unwrap()),
+        format!("TZNAME:{}", to.name()),
+        format!("END:{}", kind),
+    ]
+}
+
+/// Escape text for a property value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Lines longer than 75 bytes have to be split, with each continuation starting with a space.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+    use time_tz::timezones::db::america::DENVER;
+
+    #[test]
+    fn fold_never_splits_a_character() {
+        let line = format!("SUMMARY:{}", "Sessão às 9h €".repeat(10));
+        let folded = fold(&line);
+
+        assert!(folded.contains("\r\n "));
+        for part in folded.split("\r\n") {
+            assert!(part.len() <= 75, "{:?} is {} bytes", part, part.len());
+        }
+        assert_eq!(folded.replace("\r\n ", ""), line);
+        assert_eq!(fold("SUMMARY:short"), "SUMMARY:short");
+    }
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(
+            escape("Provo, Utah; \\ room 2\nbring a recommend"),
+            "Provo\\, Utah\\; \\\\ room 2\\nbring a recommend"
+        );
+    }
+
+    #[test]
+    fn vtimezone_covers_the_dst_change() {
+        let lines = vtimezone(
+            DENVER,
+            datetime!(2024-03-01 00:00 UTC),
+            datetime!(2024-03-31 00:00 UTC),
+        );
+        assert_eq!(
+            lines,
+            [
+                "BEGIN:VTIMEZONE",
+                "TZID:America/Denver",
+                "BEGIN:STANDARD",
+                "DTSTART:20240229T170000",
+                "TZOFFSETFROM:-0700",
+                "TZOFFSETTO:-0700",
+                "TZNAME:MST",
+                "END:STANDARD",
+                "BEGIN:DAYLIGHT",
+                "DTSTART:20240310T020000",
+                "TZOFFSETFROM:-0700",
+                "TZOFFSETTO:-0600",
+                "TZNAME:MDT",
+                "END:DAYLIGHT",
+                "END:VTIMEZONE",
+            ]
+        );
+    }
+}
@@ -1,31 +1,215 @@
-use crate::data::{Day, Temple};
+use crate::data::{AppointmentJSON, Day, Temple};
+use crate::locale::Lang;
+use crate::snapshot::ScheduleDiff;
+use crate::timezone::DisplayTimezone;
 use anyhow::Result;
 use clap::ValueEnum;
+use std::io::Write;
 
-use self::{excel::ExcelWriter, html::HTMLWriter};
+pub use self::{
+    color::Palette,
+    destination::Destination,
+    excel::ExcelWriter,
+    export::{CsvWriter, JsonWriter},
+    grid::{Cell, CellState, ScheduleGrid, ScheduleStats},
+    heatmap::{PngWriter, SvgWriter},
+    html::HTMLWriter,
+    ics::IcsWriter,
+    pdf::PDFWriter,
+    registry::FormatRegistry,
+    terminal::TerminalWriter,
+};
 
+mod appointments;
+mod color;
+mod destination;
+mod diff;
 mod excel;
+mod export;
+mod grid;
+mod heatmap;
 mod html;
+mod ics;
+mod markup;
+mod pdf;
+mod registry;
+mod terminal;
 
-#[allow(dead_code)]
-#[derive(ValueEnum, Clone)]
-pub enum ScheduleOutputFormat {
+/// How days are arranged in the HTML, PDF and Excel outputs.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// A column per day and a row per time slot
+    #[default]
+    Grid,
+
+    /// A month calendar listing the open sessions in each day
+    Calendar,
+}
+
+/// Formats a comparison of two schedule snapshots can be written in.
+#[derive(ValueEnum, Clone, Copy)]
+pub enum DiffOutputFormat {
+    Terminal,
     Html,
     Excel,
 }
 
-trait OutputWriter {
-    fn write_output(schedules: &[Day], temple: &Temple, filename: &str) -> Result<()>;
+/// Formats a list of appointments can be printed in.
+#[derive(ValueEnum, Clone, Copy)]
+pub enum AppointmentOutputFormat {
+    /// One line per appointment
+    List,
+
+    /// ASCII table
+    Table,
+
+    /// Full information in JSON
+    Json,
+
+    /// One row per appointment
+    Csv,
+
+    /// Calendar events that can be imported into any calendar app
+    Ics,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub enum PageSize {
+    #[default]
+    Letter,
+    A4,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub enum Orientation {
+    Portrait,
+    #[default]
+    Landscape,
+}
+
+/// How a schedule should be laid out. Not every writer uses every option, and options that only
+/// make sense for one format are fields on its writer instead.
+pub struct OutputOptions {
+    pub timezone: DisplayTimezone,
+    pub layout: Layout,
+    pub slot_minutes: Option<u16>,
+    pub palette: Palette,
+
+    /// Seat counts where the color of open slots steps up. If empty, a continuous gradient is used.
+    pub thresholds: Vec<u32>,
+
+    /// Highlight slots with a session that has at least this many open seats.
+    pub group_size: Option<u32>,
+
+    /// Language for dates, times and labels. Data exports like CSV and JSON aren't translated.
+    pub lang: Lang,
+
+    /// Output goes straight to a person's terminal, so the terminal writer can use color and fit
+    /// the table to its width.
+    pub interactive: bool,
+}
+
+/// Writes a schedule in one format. Writers are kept in a [`FormatRegistry`] under the name of their
+/// format, so they only take `&self` and hold any options specific to their format.
+pub trait OutputWriter {
+    /// Extension for files in this format, without the dot.
+    fn extension(&self) -> &'static str;
+
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        options: &OutputOptions,
+        output: &mut dyn Write,
+    ) -> Result<()>;
 }
 
 pub fn write_output(
     schedules: &[Day],
     temple: &Temple,
-    format: ScheduleOutputFormat,
-    filename: &str,
+    writer: &dyn OutputWriter,
+    output: &mut dyn Write,
+    options: &OutputOptions,
+) -> Result<()> {
+    // Writers lay out sessions by their date and wall clock time, so convert everything up front.
+    let schedules = Day::in_timezone(schedules, options.timezone.resolve(temple.timezone()));
+    let grid = ScheduleGrid::new(&schedules, options.slot_minutes, options.lang);
+
+    writer.write_output(&grid, temple, options, output)
+}
+
+pub fn write_diff(
+    diff: &ScheduleDiff,
+    format: DiffOutputFormat,
+    output: &mut dyn Write,
+    timezone: DisplayTimezone,
+    lang: Lang,
+) -> Result<()> {
+    match format {
+        DiffOutputFormat::Terminal => diff::write_terminal(diff, timezone, lang, output),
+        DiffOutputFormat::Html => diff::write_html(diff, timezone, lang, output),
+        DiffOutputFormat::Excel => diff::write_excel(diff, timezone, lang, output),
+    }
+}
+
+/// Times are shown in `timezone`, except in calendar events, which always use the temple's timezone
+/// so calendar apps can convert them.
+pub fn write_appointments(
+    appointments: &[AppointmentJSON],
+    format: AppointmentOutputFormat,
+    output: &mut dyn Write,
+    timezone: DisplayTimezone,
+    lang: Lang,
 ) -> Result<()> {
     match format {
-        ScheduleOutputFormat::Html => HTMLWriter::write_output(schedules, temple, filename),
-        ScheduleOutputFormat::Excel => ExcelWriter::write_output(schedules, temple, filename),
+        AppointmentOutputFormat::List => {
+            appointments::write_list(appointments, timezone, lang, output)
+        }
+        AppointmentOutputFormat::Table => {
+            appointments::write_table(appointments, timezone, lang, output)
+        }
+        AppointmentOutputFormat::Json => appointments::write_json(appointments, timezone, output),
+        AppointmentOutputFormat::Csv => appointments::write_csv(appointments, timezone, output),
+        AppointmentOutputFormat::Ics => ics::write_appointments(appointments, lang, output),
+    }
+}
+
+impl DiffOutputFormat {
+    /// File extension for output in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DiffOutputFormat::Terminal => "txt",
+            DiffOutputFormat::Html => "html",
+            DiffOutputFormat::Excel => "xlsx",
+        }
+    }
+}
+
+impl PageSize {
+    /// Width and height in millimeters, in portrait orientation.
+    pub fn dimensions(&self) -> (f32, f32) {
+        match self {
+            PageSize::Letter => (215.9, 279.4),
+            PageSize::A4 => (210.0, 297.0),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Default options, showing times in `timezone`.
+    pub(crate) fn options(timezone: DisplayTimezone) -> OutputOptions {
+        OutputOptions {
+            timezone,
+            layout: Default::default(),
+            slot_minutes: None,
+            palette: Default::default(),
+            thresholds: vec![],
+            group_size: None,
+            lang: Lang::En,
+            interactive: false,
+        }
     }
 }
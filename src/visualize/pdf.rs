@@ -0,0 +1,530 @@
+use crate::{
+    data::{Availability, Session},
+    locale::Lang,
+    Temple,
+};
+use anyhow::Result;
+use printpdf::{
+    path::PaintMode, BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Rect,
+};
+use std::io::{BufWriter, Write};
+use time::Weekday;
+use time_tz::TimeZone;
+
+use super::{
+    color::{ColorScale, Rgb},
+    grid::{CalendarMonth, Cell, CellState, ScheduleGrid, Week},
+    Layout, Orientation, OutputOptions, OutputWriter, PageSize,
+};
+
+/// Millimeters in a typographic point.
+const POINT: f32 = 0.3528;
+const MARGIN: f32 = 12.0;
+const HEADER_HEIGHT: f32 = 12.0;
+const LEGEND_HEIGHT: f32 = 10.0;
+const TIME_COLUMN_WIDTH: f32 = 20.0;
+const DAY_HEADER_HEIGHT: f32 = 8.0;
+const MIN_ROW_HEIGHT: f32 = 5.0;
+const MAX_ROW_HEIGHT: f32 = 10.0;
+
+/// Tallest a session's line in a calendar day gets, so a day with one session doesn't look stretched.
+const MAX_CALENDAR_LINE_HEIGHT: f32 = 4.5;
+
+const TEXT: Rgb = Rgb(0x21, 0x25, 0x29);
+const DAY: Rgb = Rgb(0xf1, 0xf3, 0xf5);
+const WHITE: Rgb = Rgb(0xff, 0xff, 0xff);
+const GROUP: Rgb = Rgb(0x0d, 0x6e, 0xfd);
+
+#[derive(Default)]
+pub struct PDFWriter {
+    pub page_size: PageSize,
+    pub orientation: Orientation,
+}
+
+impl OutputWriter for PDFWriter {
+    fn extension(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        options: &OutputOptions,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        let (width, height) = match (self.orientation, self.page_size.dimensions()) {
+            (Orientation::Portrait, (w, h)) => (w, h),
+            (Orientation::Landscape, (w, h)) => (h, w),
+        };
+
+        let (doc, page, layer) = PdfDocument::new(&temple.name, Mm(width), Mm(height), "Schedule");
+        let fonts = Fonts {
+            regular: doc.add_builtin_font(BuiltinFont::Helvetica)?,
+            bold: doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+        };
+
+        let colors = ColorScale::new(options, grid);
+
+        let title = Page {
+            layer: doc.get_page(page).get_layer(layer),
+            width,
+            height,
+            fonts: &fonts,
+            colors: &colors,
+            lang: grid.lang,
+        };
+        title_page(&title, grid, temple, options);
+
+        match options.layout {
+            Layout::Grid => {
+                for week in grid.weeks() {
+                    week_pages(&doc, &title, grid, temple, &week);
+                }
+            }
+            Layout::Calendar => {
+                for month in grid.months() {
+                    month_page(&doc, &title, grid, temple, &month);
+                }
+            }
+        }
+
+        doc.save(&mut BufWriter::new(output))?;
+
+        Ok(())
+    }
+}
+
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+}
+
+/// A page to draw on, with positions given in millimeters from the top left corner.
+struct Page<'a> {
+    layer: PdfLayerReference,
+    width: f32,
+    height: f32,
+    fonts: &'a Fonts,
+    colors: &'a ColorScale,
+    lang: Lang,
+}
+
+impl Page<'_> {
+    fn text(&self, text: &str, size: f32, x: f32, top: f32, bold: bool, color: Rgb) {
+        let font = if bold {
+            &self.fonts.bold
+        } else {
+            &self.fonts.regular
+        };
+        self.layer.set_fill_color(rgb(color));
+        self.layer
+            .use_text(text, size, Mm(x), Mm(self.height - top), font);
+    }
+
+    /// Text centered horizontally on `center` and vertically in the box from `top` to `top + height`.
+    fn centered_text(&self, text: &str, size: f32, center: f32, top: f32, height: f32, color: Rgb) {
+        let baseline = top + height / 2.0 + size * POINT * 0.35;
+        self.text(
+            text,
+            size,
+            center - text_width(text, size) / 2.0,
+            baseline,
+            false,
+            color,
+        );
+    }
+
+    fn rect(&self, x: f32, top: f32, width: f32, height: f32, fill: Rgb) {
+        self.layer.set_fill_color(rgb(fill));
+        self.layer.set_outline_color(rgb(WHITE));
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_rect(
+            Rect::new(
+                Mm(x),
+                Mm(self.height - top - height),
+                Mm(x + width),
+                Mm(self.height - top),
+            )
+            .with_mode(PaintMode::FillStroke),
+        );
+    }
+
+    /// Outline just inside a box to mark slots with room for the whole group.
+    fn group_outline(&self, x: f32, top: f32, width: f32, height: f32) {
+        let inset = 0.6;
+        self.layer.set_outline_color(rgb(GROUP));
+        self.layer.set_outline_thickness(2.5);
+        self.layer.add_rect(
+            Rect::new(
+                Mm(x + inset),
+                Mm(self.height - top - height + inset),
+                Mm(x + width - inset),
+                Mm(self.height - top - inset),
+            )
+            .with_mode(PaintMode::Stroke),
+        );
+    }
+
+    /// A row of colored boxes explaining what each cell color means.
+    fn legend(&self, top: f32) {
+        let mut x = MARGIN;
+        for entry in self.colors.legend() {
+            self.rect(x, top, 4.0, 4.0, entry.fill);
+            self.text(&entry.label, 9.0, x + 6.0, top + 3.2, false, TEXT);
+            x += 6.0 + text_width(&entry.label, 9.0) + 8.0;
+        }
+        if let Some(size) = self.colors.group_size() {
+            let label = self.lang.room_for(size);
+            self.rect(x, top, 4.0, 4.0, WHITE);
+            self.group_outline(x, top, 4.0, 4.0);
+            self.text(&label, 9.0, x + 6.0, top + 3.2, false, TEXT);
+            x += 6.0 + text_width(&label, 9.0) + 8.0;
+        }
+        let special = format!("* {}", self.lang.labels().special_session);
+        self.text(&special, 9.0, x, top + 3.2, false, TEXT);
+    }
+}
+
+fn title_page(page: &Page, grid: &ScheduleGrid, temple: &Temple, options: &OutputOptions) {
+    let mut top = MARGIN + 30.0;
+    page.text(&temple.name, 28.0, MARGIN, top, true, TEXT);
+
+    top += 12.0;
+    page.text(
+        grid.lang.labels().available_slots,
+        14.0,
+        MARGIN,
+        top,
+        false,
+        TEXT,
+    );
+
+    if let (Some(first), Some(last)) = (grid.columns.first(), grid.columns.last()) {
+        top += 10.0;
+        page.text(
+            &grid.lang.date_range(*first, *last),
+            12.0,
+            MARGIN,
+            top,
+            false,
+            TEXT,
+        );
+    }
+
+    top += 7.0;
+    page.text(
+        &grid
+            .lang
+            .times_shown_in(options.timezone.resolve(temple.timezone()).name()),
+        12.0,
+        MARGIN,
+        top,
+        false,
+        TEXT,
+    );
+
+    top += 6.0;
+    for line in wrap(&grid.summary(), 11.0, page.width - 2.0 * MARGIN) {
+        top += 6.0;
+        page.text(&line, 11.0, MARGIN, top, false, TEXT);
+    }
+
+    page.legend(top + 12.0);
+}
+
+/// One page per week, or more if the week has too many rows to fit on a page.
+fn week_pages(
+    doc: &PdfDocumentReference,
+    title_page: &Page,
+    grid: &ScheduleGrid,
+    temple: &Temple,
+    week: &Week,
+) {
+    let (width, height) = (title_page.width, title_page.height);
+    let table_top = MARGIN + HEADER_HEIGHT;
+    let available = height - table_top - DAY_HEADER_HEIGHT - LEGEND_HEIGHT - MARGIN;
+    let row_height =
+        (available / grid.rows.len().max(1) as f32).clamp(MIN_ROW_HEIGHT, MAX_ROW_HEIGHT);
+    let rows_per_page = ((available / row_height) as usize).max(1);
+    let day_width = (width - 2.0 * MARGIN - TIME_COLUMN_WIDTH) / 7.0;
+
+    let rows: Vec<usize> = (0..grid.rows.len()).collect();
+    let chunks: Vec<&[usize]> = rows.chunks(rows_per_page).collect();
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let (page, layer) = doc.add_page(Mm(width), Mm(height), "Schedule");
+        let page = Page {
+            layer: doc.get_page(page).get_layer(layer),
+            width,
+            height,
+            fonts: title_page.fonts,
+            colors: title_page.colors,
+            lang: title_page.lang,
+        };
+
+        let mut title = format!("{} - {}", temple.name, grid.lang.week_of(week.start));
+        if chunks.len() > 1 {
+            title.push(' ');
+            title.push_str(&grid.lang.page_of(index + 1, chunks.len()));
+        }
+        page.text(&title, 14.0, MARGIN, MARGIN + 6.0, true, TEXT);
+
+        for &column in &week.columns {
+            let offset = (grid.columns[column] - week.start).whole_days() as f32;
+            let x = MARGIN + TIME_COLUMN_WIDTH + offset * day_width;
+            page.centered_text(
+                &grid.column_label(column),
+                9.0,
+                x + day_width / 2.0,
+                table_top,
+                DAY_HEADER_HEIGHT,
+                TEXT,
+            );
+
+            let cells = grid.column(column);
+            for (i, &row) in chunk.iter().enumerate() {
+                let top = table_top + DAY_HEADER_HEIGHT + i as f32 * row_height;
+                draw_cell(&page, &cells[row], grid, row, x, top, day_width, row_height);
+            }
+        }
+
+        for (i, &row) in chunk.iter().enumerate() {
+            let top = table_top + DAY_HEADER_HEIGHT + i as f32 * row_height;
+            page.centered_text(
+                &grid.row_label(row),
+                8.0,
+                MARGIN + TIME_COLUMN_WIDTH / 2.0,
+                top,
+                row_height,
+                TEXT,
+            );
+        }
+
+        page.legend(height - MARGIN - LEGEND_HEIGHT + 4.0);
+    }
+}
+
+/// A page per month laid out like a wall calendar, with the open sessions listed in each day.
+fn month_page(
+    doc: &PdfDocumentReference,
+    title_page: &Page,
+    grid: &ScheduleGrid,
+    temple: &Temple,
+    month: &CalendarMonth,
+) {
+    let (width, height) = (title_page.width, title_page.height);
+    let (page, layer) = doc.add_page(Mm(width), Mm(height), "Schedule");
+    let page = Page {
+        layer: doc.get_page(page).get_layer(layer),
+        width,
+        height,
+        fonts: title_page.fonts,
+        colors: title_page.colors,
+        lang: title_page.lang,
+    };
+
+    let title = format!("{} - {}", temple.name, grid.lang.month_year(month.start));
+    page.text(&title, 14.0, MARGIN, MARGIN + 6.0, true, TEXT);
+
+    let table_top = MARGIN + HEADER_HEIGHT;
+    let day_width = (width - 2.0 * MARGIN) / 7.0;
+    let available = height - table_top - DAY_HEADER_HEIGHT - LEGEND_HEIGHT - MARGIN;
+    let week_height = available / month.weeks.len().max(1) as f32;
+
+    for weekday in 0..7 {
+        page.centered_text(
+            grid.lang.weekday_short(Weekday::Sunday.nth_next(weekday)),
+            9.0,
+            MARGIN + (weekday as f32 + 0.5) * day_width,
+            table_top,
+            DAY_HEADER_HEIGHT,
+            TEXT,
+        );
+    }
+
+    for (i, week) in month.weeks.iter().enumerate() {
+        let top = table_top + DAY_HEADER_HEIGHT + i as f32 * week_height;
+        for (weekday, date) in week.iter().enumerate() {
+            if let Some(date) = date {
+                let x = MARGIN + weekday as f32 * day_width;
+                page.rect(x, top, day_width, week_height, DAY);
+                page.text(&date.day().to_string(), 9.0, x + 1.5, top + 4.0, true, TEXT);
+                if let Some(column) = grid.column_of(*date) {
+                    draw_day(
+                        &page,
+                        grid,
+                        column,
+                        x,
+                        top + 5.5,
+                        day_width,
+                        week_height - 6.5,
+                    );
+                }
+            }
+        }
+    }
+
+    page.legend(height - MARGIN - LEGEND_HEIGHT + 4.0);
+}
+
+/// Each open session of a day on its own colored line, or a single line saying the day is full or closed.
+fn draw_day(
+    page: &Page,
+    grid: &ScheduleGrid,
+    column: usize,
+    x: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+) {
+    let labels = grid.lang.labels();
+    let lines: Vec<(String, CellState, bool)> = match grid.day_state(column) {
+        CellState::Full => vec![(labels.full.to_string(), CellState::Full, false)],
+        CellState::Closed => vec![(labels.closed.to_string(), CellState::Closed, false)],
+        CellState::None => vec![],
+        CellState::Open(_) => grid
+            .open_sessions(column)
+            .into_iter()
+            .map(|session| {
+                let seats = session.details.remaining_seats();
+                let mut line =
+                    format!("{}  {}", grid.lang.time(session.time.local().time()), seats);
+                if session.details.special_session {
+                    line.push('*');
+                }
+                let group = matches!(page.colors.group_size(), Some(size) if seats >= size);
+                (line, CellState::Open(seats), group)
+            })
+            .collect(),
+    };
+    if lines.is_empty() {
+        return;
+    }
+
+    let line_height = (height / lines.len() as f32).min(MAX_CALENDAR_LINE_HEIGHT);
+    let widest = lines
+        .iter()
+        .map(|(line, _, _)| text_width(line, 1.0))
+        .fold(0.0, f32::max);
+    let size = (line_height / POINT * 0.7)
+        .min((width - 3.0) / widest)
+        .min(8.0);
+
+    for (i, (line, state, group)) in lines.iter().enumerate() {
+        let line_top = top + i as f32 * line_height;
+        let fill = page.colors.fill(*state);
+        page.rect(x + 1.0, line_top, width - 2.0, line_height, fill);
+        if *group {
+            page.group_outline(x + 1.0, line_top, width - 2.0, line_height);
+        }
+        page.centered_text(
+            line,
+            size,
+            x + width / 2.0,
+            line_top,
+            line_height,
+            fill.text_color(),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_cell(
+    page: &Page,
+    cell: &Cell,
+    grid: &ScheduleGrid,
+    row: usize,
+    x: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+) {
+    let fill = page.colors.fill(cell.state);
+    page.rect(x, top, width, height, fill);
+    if let Some(size) = page.colors.group_size() {
+        if cell.fits_group(size) {
+            page.group_outline(x, top, width, height);
+        }
+    }
+
+    let show_times = cell.shows_session_times(grid.rows[row]);
+    let lines: Vec<_> = cell
+        .sessions
+        .iter()
+        .map(|s| session_content(s, show_times, grid.lang))
+        .collect();
+    if lines.is_empty() {
+        return;
+    }
+
+    // Shrink the text until every line fits in the cell.
+    let line_height = height / lines.len() as f32;
+    let widest = lines.iter().map(|l| text_width(l, 1.0)).fold(0.0, f32::max);
+    let size = (line_height / POINT * 0.7)
+        .min((width - 2.0) / widest)
+        .min(9.0);
+
+    for (i, line) in lines.iter().enumerate() {
+        page.centered_text(
+            line,
+            size,
+            x + width / 2.0,
+            top + i as f32 * line_height,
+            line_height,
+            fill.text_color(),
+        );
+    }
+}
+
+/// Seat count for a session, with its start time if needed and a marker if it's a special session.
+fn session_content(session: &Session, show_time: bool, lang: Lang) -> String {
+    let mut content = match session.details.availability() {
+        Availability::Open(remaining) => remaining.to_string(),
+        Availability::Full => lang.labels().full.to_string(),
+        Availability::Closed => lang.labels().closed.to_string(),
+    };
+    if show_time {
+        content = format!("{}: {}", lang.time(session.time.local().time()), content);
+    }
+    if session.details.special_session {
+        content.push('*');
+    }
+
+    content
+}
+
+/// Rough width of text in millimeters. The built in fonts don't come with metrics, but half the font
+/// size per character is close enough for Helvetica to center and fit text.
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * 0.5 * POINT
+}
+
+fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && text_width(&format!("{line} {word}"), size) > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn rgb(Rgb(r, g, b): Rgb) -> Color {
+    Color::Rgb(printpdf::Rgb::new(
+        r as f32 / 255.0,
+        g as f32 / 255.0,
+        b as f32 / 255.0,
+        None,
+    ))
+}
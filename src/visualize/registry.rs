@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use super::{
+    CsvWriter, ExcelWriter, HTMLWriter, IcsWriter, JsonWriter, OutputWriter, PDFWriter, PngWriter,
+    SvgWriter, TerminalWriter,
+};
+
+/// Writers by the name of the format they write. The default registry has every built in format
+/// with its default options. Register a writer under an existing name to replace it, for example to
+/// change its options, or under a new name to add a format.
+pub struct FormatRegistry {
+    writers: BTreeMap<String, Box<dyn OutputWriter>>,
+}
+
+impl FormatRegistry {
+    /// A registry without any formats.
+    pub fn empty() -> Self {
+        Self {
+            writers: BTreeMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, name: &str, writer: impl OutputWriter + 'static) -> &mut Self {
+        self.writers.insert(name.to_string(), Box::new(writer));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn OutputWriter> {
+        self.writers.get(name).map(|writer| writer.as_ref())
+    }
+
+    /// Names of the registered formats, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.writers.keys().map(String::as_str)
+    }
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register("html", HTMLWriter::default())
+            .register("excel", ExcelWriter)
+            .register("pdf", PDFWriter::default())
+            .register("svg", SvgWriter)
+            .register("png", PngWriter)
+            .register("csv", CsvWriter)
+            .register("json", JsonWriter)
+            .register("ics", IcsWriter::default())
+            .register("terminal", TerminalWriter);
+        registry
+    }
+}
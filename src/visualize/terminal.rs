@@ -0,0 +1,160 @@
+use crate::Temple;
+use anyhow::Result;
+use std::io::Write;
+use term_table::{
+    row::Row,
+    table_cell::{Alignment, TableCell},
+};
+use terminal_size::{terminal_size_of, Width};
+
+use super::{
+    grid::{Cell, CellState, ScheduleGrid},
+    OutputOptions, OutputWriter,
+};
+
+/// Characters taken up by the time column and by each day column, including padding and borders.
+const TIME_COLUMN_WIDTH: usize = 12;
+const DAY_COLUMN_WIDTH: usize = 13;
+const CELL_WIDTH: usize = 10;
+
+/// 256 color backgrounds from few open seats to many.
+const SEAT_SHADES: [u8; 5] = [194, 157, 120, 71, 28];
+const FULL: &str = "97;48;5;160";
+const CLOSED: &str = "97;48;5;238";
+
+/// Prints the schedule as plain text tables. Each week gets its own table, split further if it's wider
+/// than the terminal.
+pub struct TerminalWriter;
+
+impl OutputWriter for TerminalWriter {
+    fn extension(&self) -> &'static str {
+        "txt"
+    }
+
+    fn write_output(
+        &self,
+        grid: &ScheduleGrid,
+        temple: &Temple,
+        options: &OutputOptions,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        // Colors and the terminal width only make sense when a person is looking at the output.
+        let color = options.interactive && std::env::var_os("NO_COLOR").is_none();
+        let size = if options.interactive {
+            terminal_size_of(std::io::stdout())
+        } else {
+            None
+        };
+        let days_per_table = match size {
+            Some((Width(width), _)) => {
+                ((width as usize).saturating_sub(TIME_COLUMN_WIDTH) / DAY_COLUMN_WIDTH).clamp(1, 7)
+            }
+            None => 7,
+        };
+        let max_seats = grid.max_open_seats().max(1);
+
+        writeln!(output, "{}", paint(&temple.name, "1", color))?;
+        writeln!(output, "{}", grid.lang.labels().available_slots)?;
+
+        for week in grid.weeks() {
+            for columns in week.columns.chunks(days_per_table) {
+                writeln!(output)?;
+                writeln!(output, "{}", grid.lang.week_of(week.start))?;
+
+                let mut table = term_table::Table::new();
+                table.style = term_table::TableStyle::thin();
+
+                let mut header = vec![TableCell::new("")];
+                header.extend(columns.iter().map(|&c| centered(grid.column_label(c))));
+                table.add_row(Row::new(header));
+
+                // Leave out rows that have no sessions on any of these days to keep the table short.
+                for row in 0..grid.rows.len() {
+                    let cells: Vec<&Cell> = columns.iter().map(|&c| &grid.column(c)[row]).collect();
+                    if cells.iter().all(|cell| cell.state == CellState::None) {
+                        continue;
+                    }
+
+                    let mut table_row = vec![TableCell::new(grid.row_label(row))];
+                    table_row.extend(
+                        cells
+                            .into_iter()
+                            .map(|cell| centered(cell_text(grid, cell, max_seats, color))),
+                    );
+                    table.add_row(Row::new(table_row));
+                }
+
+                write!(output, "{}", table.render())?;
+                writeln!(output)?;
+            }
+        }
+
+        let labels = grid.lang.labels();
+        let special = format!("* {}", labels.special_session.to_lowercase());
+        writeln!(output)?;
+        if color {
+            writeln!(
+                output,
+                "{} {}  {}  {}  {}",
+                paint(
+                    &format!("{:^5}", max_seats),
+                    &shade(max_seats, max_seats),
+                    color
+                ),
+                grid.lang.darker_has_more(),
+                paint(labels.full, FULL, color),
+                paint(labels.closed, CLOSED, color),
+                special
+            )?;
+        } else {
+            writeln!(output, "{}", special)?;
+        }
+        writeln!(output, "{}", grid.summary())?;
+
+        Ok(())
+    }
+}
+
+fn cell_text(grid: &ScheduleGrid, cell: &Cell, max_seats: u32, color: bool) -> String {
+    let special = if cell.sessions.iter().any(|s| s.details.special_session) {
+        "*"
+    } else {
+        ""
+    };
+
+    let (text, style) = match cell.state {
+        CellState::Open(seats) => (format!("{seats}{special}"), shade(seats, max_seats)),
+        CellState::Full => (
+            format!("{}{special}", grid.lang.labels().full),
+            FULL.to_string(),
+        ),
+        CellState::Closed => (
+            format!("{}{special}", grid.lang.labels().closed),
+            CLOSED.to_string(),
+        ),
+        CellState::None => return String::new(),
+    };
+
+    // Pad before coloring so the background fills the whole cell.
+    paint(&format!("{text:^CELL_WIDTH$}"), &style, color)
+}
+
+/// Green background for a number of open seats, darker the closer it is to the most in the schedule.
+fn shade(seats: u32, max_seats: u32) -> String {
+    let index = ((seats * SEAT_SHADES.len() as u32).saturating_sub(1) / max_seats) as usize;
+    let index = index.min(SEAT_SHADES.len() - 1);
+    let foreground = if index < 3 { 30 } else { 97 };
+    format!("{};48;5;{}", foreground, SEAT_SHADES[index])
+}
+
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{style}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+fn centered<'a>(text: String) -> TableCell<'a> {
+    TableCell::new_with_alignment(text, 1, Alignment::Center)
+}